@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use tuckey::corpus::*;
+use tuckey::meta::*;
+use tuckey::sequence::*;
+use tuckey::token::*;
+
+// The parser generated from the same grammar, as checked in for the tests.
+#[allow(dead_code)]
+mod calc_parser {
+    include!("../src/tests/calc_parser.rs");
+}
+
+fn calc(c: &mut Criterion) {
+    let grammar = include_str!("../src/tests/calc.tuckey");
+    let refs = eval_rule_set(grammar);
+    let expr = RefSeq::new("expr".to_string());
+    let compiled = CompiledSeq(calc_parser::expr);
+    let text = (1..200)
+        .map(|i| format!("({i} + {}) * {} / 7", i * 3, i % 9 + 1))
+        .collect::<Vec<_>>()
+        .join(" - ");
+    let corpus = Corpus::make(&text);
+    assert_eq!(
+        expr.parse_corpus(&corpus, &refs).unwrap().graph(),
+        compiled.parse(&corpus).unwrap().graph()
+    );
+
+    let mut group = c.benchmark_group("calc");
+    group.bench_function("interpreted", |b| {
+        b.iter(|| expr.parse_corpus(&corpus, &refs).unwrap())
+    });
+    group.bench_function("generated", |b| b.iter(|| compiled.parse(&corpus).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, calc);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use tuckey::corpus::*;
+use tuckey::meta::*;
+use tuckey::sequence::*;
+
+// Every alternative of `expr` reparses `term`, so without the memo table the
+// work doubles with each level of nesting; with it, it should grow linearly.
+fn memo(c: &mut Criterion) {
+    let refs = eval_rule_set(
+        "
+    expr = (term & '+' & expr) | (term & '-' & expr) | term
+    term = ('(' & expr & ')') | 0..9
+    ",
+    );
+    let seq = RefSeq::new("expr".to_string());
+
+    let mut group = c.benchmark_group("memo");
+    for depth in [100, 200, 400, 800] {
+        let text = "(".repeat(depth) + "1" + &")".repeat(depth);
+        let corpus = Corpus::make(&text);
+        group.bench_with_input(BenchmarkId::new("memoized", depth), &corpus, |b, corpus| {
+            b.iter(|| {
+                let mut ctx = ParseContext::new(&refs).with_memo(MemoOptions::new());
+                seq.parse_corpus_with(corpus, &mut ctx).unwrap()
+            })
+        });
+    }
+    for depth in [4, 8, 12] {
+        let text = "(".repeat(depth) + "1" + &")".repeat(depth);
+        let corpus = Corpus::make(&text);
+        group.bench_with_input(BenchmarkId::new("plain", depth), &corpus, |b, corpus| {
+            b.iter(|| seq.parse_corpus(corpus, &refs).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, memo);
+criterion_main!(benches);
@@ -0,0 +1,188 @@
+use core::ops::Range;
+use std::any::Any;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::ast::AstError;
+use crate::corpus::*;
+use crate::sequence::*;
+use crate::token::*;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionError {
+    Parse(ParseError),
+    Action(AstError),
+}
+
+impl Display for ActionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionError::Parse(err) => write!(f, "{err}"),
+            ActionError::Action(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for ActionError {}
+
+impl From<ParseError> for ActionError {
+    fn from(err: ParseError) -> Self {
+        ActionError::Parse(err)
+    }
+}
+
+impl From<AstError> for ActionError {
+    fn from(err: AstError) -> Self {
+        ActionError::Action(err)
+    }
+}
+
+// The values of the rules with actions found inside a rule's match, in order.
+// Each is labelled with the nearest name above it within the rule, so that in
+// `multExpr:lhs + (oper:oper + multExpr:rhs)*:rhs_s` the values are labelled
+// `lhs`, then `oper` and `rhs` in turn.
+pub struct Values<'t, T> {
+    span: Option<Range<usize>>,
+    items: Vec<(&'t str, T)>,
+}
+
+impl<'t, T> Values<'t, T> {
+    // Removes and returns the first value labelled `name`.
+    pub fn take(&mut self, name: &str) -> Result<T, AstError> {
+        match self.items.iter().position(|(label, _)| *label == name) {
+            Some(index) => Ok(self.items.remove(index).1),
+            None => Err(AstError {
+                message: format!("no value for `{name}`"),
+                span: self.span.clone(),
+            }),
+        }
+    }
+
+    // Removes and returns every value labelled `name`.
+    pub fn take_all(&mut self, name: &str) -> Vec<T> {
+        let (taken, rest) = std::mem::take(&mut self.items)
+            .into_iter()
+            .partition(|(label, _)| *label == name);
+        self.items = rest;
+        taken.into_iter().map(|(_, value)| value).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn into_vec(self) -> Vec<(&'t str, T)> {
+        self.items
+    }
+}
+
+type ActionFn<'f, T> =
+    Box<dyn for<'t, 'a> Fn(&'t Token<'a>, Values<'t, T>) -> Result<T, AstError> + 'f>;
+
+// Closures keyed on rule names that turn a parse straight into a value. Each
+// gets the rule's token and the values of the rules with actions inside it.
+// Actions run as soon as their rule matches, so one may run for a match that
+// is later dropped; they should only depend on what they are given. The
+// first error stops the parse.
+pub struct Actions<'f, T> {
+    handlers: HashMap<String, ActionFn<'f, T>>,
+}
+
+impl<T: 'static> Default for Actions<'_, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'f, T: 'static> Actions<'f, T> {
+    pub fn new() -> Self {
+        Self {
+            handlers: HashMap::new(),
+        }
+    }
+
+    pub fn on(
+        mut self,
+        rule: impl Into<String>,
+        action: impl for<'t, 'a> Fn(&'t Token<'a>, Values<'t, T>) -> Result<T, AstError> + 'f,
+    ) -> Self {
+        self.handlers.insert(rule.into(), Box::new(action));
+        self
+    }
+
+    // Sets up `ctx` to run the actions, for `finish`.
+    pub fn context<'a>(&'a self, ctx: ParseContext<'a>) -> ParseContext<'a> {
+        ctx.with_actions(self)
+    }
+
+    pub fn parse<'a>(
+        &'a self,
+        seq: &'a dyn Sequence,
+        corpus: &Corpus<'a>,
+        refs: &'a RefMap,
+    ) -> Result<T, ActionError> {
+        let mut ctx = self.context(ParseContext::new(refs));
+        let parsed = seq.parse_corpus_with(corpus, &mut ctx);
+        self.finish(parsed, &mut ctx)
+    }
+
+    // Takes the value of a parse made with a context from `context`. The
+    // tree must hold exactly one value at the top, usually that of the rule
+    // the parse started from.
+    pub fn finish<'a>(
+        &self,
+        parsed: Result<Token<'a>, ParseError>,
+        ctx: &mut ParseContext<'a>,
+    ) -> Result<T, ActionError> {
+        if let Some(error) = ctx.take_action_error() {
+            return Err(error.into());
+        }
+        let token = parsed?;
+        let mut values = ctx.take_values(&token)?;
+        match values.len() {
+            1 => Ok(downcast(values.pop().unwrap().1)),
+            n => Err(AstError::at(
+                format!("expected one value for the whole input, found {n}"),
+                &token,
+            )
+            .into()),
+        }
+    }
+}
+
+impl<T: 'static> RuleActions for Actions<'_, T> {
+    fn handles(&self, rule: &str) -> bool {
+        self.handlers.contains_key(rule)
+    }
+
+    fn run<'t>(
+        &self,
+        rule: &str,
+        token: &'t Token<'_>,
+        values: LabelledValues<'t>,
+    ) -> Result<Box<dyn Any>, AstError> {
+        let values = Values {
+            span: token.content_range(),
+            items: values
+                .into_iter()
+                .map(|(label, value)| (label, downcast(value)))
+                .collect(),
+        };
+        Ok(Box::new(self.handlers[rule](token, values)?))
+    }
+}
+
+// Every value on a context comes from the same `Actions`, so has its type.
+fn downcast<T: 'static>(value: Box<dyn Any>) -> T {
+    *value
+        .downcast()
+        .expect("values from one `Actions` share a type")
+}
@@ -0,0 +1,133 @@
+use super::*;
+use crate::meta::*;
+use crate::tests::left_parser;
+
+fn left_actions() -> Actions<'static, i64> {
+    Actions::new()
+        .on("posInt", |int, _| {
+            int.content()
+                .parse()
+                .map_err(|_| AstError::at("not a number", int))
+        })
+        .on("expr", binary)
+        .on("multExpr", binary)
+}
+
+fn binary(expr: &Token<'_>, mut values: Values<'_, i64>) -> Result<i64, AstError> {
+    let Some(oper) = expr.select_first("binary/oper").unwrap() else {
+        return values.take("single");
+    };
+    let (lhs, rhs) = (values.take("lhs")?, values.take("rhs")?);
+    Ok(match oper.content() {
+        "+" => lhs + rhs,
+        "-" => lhs - rhs,
+        "*" => lhs * rhs,
+        _ => lhs / rhs,
+    })
+}
+
+#[test]
+pub fn left_recursive_test() {
+    let seqs = eval_rule_set(include_str!("../tests/left.tuckey"));
+    let expr = RefSeq::new("expr".to_string());
+    let actions = left_actions();
+    let eval = |text| actions.parse(&expr, &Corpus::make(text), &seqs);
+    assert_eq!(eval("8 / 2 / 2"), Ok(2));
+    assert_eq!(eval("2 + 3 * 4 - 6 / 3"), Ok(12));
+    assert!(matches!(eval("2 + * 4"), Err(ActionError::Parse(_))));
+}
+
+#[test]
+pub fn compiled_test() {
+    let actions = left_actions();
+    let corpus = Corpus::make("10 - 2 * 3");
+    let mut ctx = actions.context(ParseContext::without_refs());
+    let parsed = CompiledSeq(left_parser::expr).parse_with(&corpus, &mut ctx);
+    assert_eq!(actions.finish(parsed, &mut ctx), Ok(4));
+}
+
+#[test]
+pub fn memo_test() {
+    // Memoized matches are reused after the matches that took their values
+    // were dropped, and their values are worked out again.
+    let seqs = eval_rule_set(
+        "
+    digit = 0..9
+    pair = digit:n & digit:n
+    wrapped = pair:p & _
+    main = (wrapped:p & 'x') | (pair:p & 'y')
+    ",
+    );
+    let actions = Actions::new()
+        .on("digit", |digit, _| Ok(digit.content().to_string()))
+        .on("pair", |_, values| {
+            Ok(values.into_vec().into_iter().map(|(_, v)| v).collect())
+        })
+        .on("wrapped", |_, mut values| values.take("p"))
+        .on("main", |_, mut values| values.take("p"));
+    let main = RefSeq::new("main".to_string());
+    let corpus = Corpus::make("12y");
+    let mut ctx = actions.context(ParseContext::new(&seqs).with_memo(MemoOptions::new()));
+    let parsed = main.parse_corpus_with(&corpus, &mut ctx);
+    assert_eq!(actions.finish(parsed, &mut ctx), Ok("12".to_string()));
+}
+
+#[test]
+pub fn error_test() {
+    // A failed action ends the parse, even where another alternative would
+    // have matched.
+    let seqs = eval_rule_set(
+        "
+    digit = 0..9
+    main = (digit:d & 'x') | (digit & 'y')
+    ",
+    );
+    let actions = Actions::new()
+        .on("digit", |digit, _| match digit.content() {
+            "0" => Err(AstError::at("zero", digit)),
+            _ => Ok(1),
+        })
+        .on("main", |_, values| Ok(values.len()));
+    let main = RefSeq::new("main".to_string());
+    assert_eq!(actions.parse(&main, &Corpus::make("1y"), &seqs), Ok(1));
+    assert_eq!(
+        actions
+            .parse(&main, &Corpus::make("0y"), &seqs)
+            .unwrap_err()
+            .to_string(),
+        "zero at 0..1"
+    );
+}
+
+#[test]
+pub fn values_test() {
+    let seqs = eval_rule_set(
+        "
+    digit = 0..9
+    list = digit:first & (',' & digit:rest)*
+    ",
+    );
+    let actions = Actions::new()
+        .on("digit", |digit, _| Ok(digit.content().to_string()))
+        .on("list", |list, mut values| {
+            // Rules with actions leave no extra branches in the token.
+            assert_eq!(list.children().len(), 2);
+            let first = values.take("first")?;
+            let rest = values.take_all("rest");
+            assert!(values.is_empty());
+            assert!(values.take("first").is_err());
+            Ok(format!("{first}|{}", rest.join("")))
+        });
+    let list = RefSeq::new("list".to_string());
+    let value = actions.parse(&list, &Corpus::make("1,2,3"), &seqs);
+    assert_eq!(value, Ok("1|23".to_string()));
+
+    let digits = Actions::new().on("digit", |digit, _| Ok(digit.content().to_string()));
+    assert_eq!(
+        digits
+            .parse(&list, &Corpus::make("1,2"), &seqs)
+            .unwrap_err()
+            .to_string(),
+        "expected one value for the whole input, found 2 at 0..3"
+    );
+}
@@ -0,0 +1,178 @@
+use core::ops::Range;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::token::*;
+
+#[cfg(feature = "derive")]
+pub use tuckey_derive::FromToken;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AstError {
+    pub message: String,
+    pub span: Option<Range<usize>>,
+}
+
+impl AstError {
+    pub fn at(message: impl Into<String>, token: &Token<'_>) -> Self {
+        Self {
+            message: message.into(),
+            span: token.content_range(),
+        }
+    }
+
+    pub fn missing_child(name: &str, token: &Token<'_>) -> Self {
+        Self::at(format!("missing child `{name}`"), token)
+    }
+}
+
+impl Display for AstError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{} at {}..{}", self.message, span.start, span.end),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for AstError {}
+
+pub trait FromToken<'a>: Sized {
+    fn from_token(token: &Token<'a>) -> Result<Self, AstError>;
+
+    // Used when a named child is absent; only optional types have a value
+    // for that.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+pub fn content<'a>(token: &Token<'a>) -> &'a str {
+    token
+        .content_range()
+        .map_or("", |range| &token.source[range])
+}
+
+pub fn span(token: &Token<'_>) -> Range<usize> {
+    token.content_range().unwrap_or_default()
+}
+
+pub fn children<'t, 'a>(
+    token: &'t Token<'a>,
+    name: &'t str,
+) -> impl Iterator<Item = &'t Token<'a>> + 't {
+    token.children_named(name)
+}
+
+pub fn child<'t, 'a>(token: &'t Token<'a>, name: &str) -> Option<&'t Token<'a>> {
+    token.child(name)
+}
+
+pub fn from_child<'a, T: FromToken<'a>>(token: &Token<'a>, name: &str) -> Result<T, AstError> {
+    match child(token, name) {
+        Some(child) => T::from_token(child),
+        None => T::from_missing().ok_or_else(|| AstError::missing_child(name, token)),
+    }
+}
+
+pub fn from_children<'a, T: FromToken<'a>, C: FromIterator<T>>(
+    token: &Token<'a>,
+    name: &str,
+) -> Result<C, AstError> {
+    children(token, name).map(T::from_token).collect()
+}
+
+impl<'a> FromToken<'a> for Token<'a> {
+    fn from_token(token: &Token<'a>) -> Result<Self, AstError> {
+        Ok(token.clone())
+    }
+}
+
+impl<'a> FromToken<'a> for &'a str {
+    fn from_token(token: &Token<'a>) -> Result<Self, AstError> {
+        Ok(content(token))
+    }
+}
+
+impl<'a> FromToken<'a> for String {
+    fn from_token(token: &Token<'a>) -> Result<Self, AstError> {
+        Ok(content(token).to_string())
+    }
+}
+
+macro_rules! parse_primitive {
+    ($($ty:ty : $what:literal,)*) => {
+        $(
+            impl<'a> FromToken<'a> for $ty {
+                fn from_token(token: &Token<'a>) -> Result<Self, AstError> {
+                    content(token).trim().parse().map_err(|_| {
+                        AstError::at(
+                            format!("expected {}, found `{}`", $what, content(token)),
+                            token,
+                        )
+                    })
+                }
+            }
+        )*
+    };
+}
+
+parse_primitive! {
+    bool: "a boolean",
+    i8: "an integer",
+    i16: "an integer",
+    i32: "an integer",
+    i64: "an integer",
+    i128: "an integer",
+    isize: "an integer",
+    u8: "an integer",
+    u16: "an integer",
+    u32: "an integer",
+    u64: "an integer",
+    u128: "an integer",
+    usize: "an integer",
+    f32: "a number",
+    f64: "a number",
+    char: "a single character",
+}
+
+impl<'a, T: FromToken<'a>> FromToken<'a> for Box<T> {
+    fn from_token(token: &Token<'a>) -> Result<Self, AstError> {
+        T::from_token(token).map(Box::new)
+    }
+}
+
+// `OptSeq` leaves an empty branch when nothing matched and wraps the match
+// in a single child otherwise. Rules and choices also leave single-child
+// branches, but those are tagged and belong to `T`.
+impl<'a, T: FromToken<'a>> FromToken<'a> for Option<T> {
+    fn from_token(token: &Token<'a>) -> Result<Self, AstError> {
+        match &token.data {
+            TokenData::Branch(children) if children.is_empty() => Ok(None),
+            TokenData::Branch(children)
+                if children.len() == 1 && token.rule.is_none() && token.alt.is_none() =>
+            {
+                T::from_token(&children[0].1).map(Some)
+            }
+            _ => T::from_token(token).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+// Every child of a branch, whatever its name, as produced by `*` and `+`.
+impl<'a, T: FromToken<'a>> FromToken<'a> for Vec<T> {
+    fn from_token(token: &Token<'a>) -> Result<Self, AstError> {
+        match &token.data {
+            TokenData::Leaf(_) => Ok(vec![]),
+            TokenData::Error(_, error) => Err(AstError::at(error.message(), token)),
+            TokenData::Branch(children) => children
+                .iter()
+                .map(|(_, child)| T::from_token(child))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use tuckey::codegen::{write_ast, write_parser};
+
+const USAGE: &str = "usage: tuckey <ast|parser> <grammar.tuckey> [-o <out.rs>]";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (kind, grammar, out) = match args.as_slice() {
+        [kind, grammar] => (kind, PathBuf::from(grammar), None),
+        [kind, grammar, flag, out] if flag == "-o" => {
+            (kind, PathBuf::from(grammar), Some(PathBuf::from(out)))
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let write = match kind.as_str() {
+        "ast" => write_ast,
+        "parser" => write_parser,
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    // Without `-o`, the output goes next to the grammar, as `<stem>_<kind>.rs`.
+    let out = out.unwrap_or_else(|| {
+        let stem = grammar
+            .file_stem()
+            .map_or("grammar".into(), |stem| stem.to_string_lossy());
+        grammar.with_file_name(format!("{stem}_{kind}.rs"))
+    });
+    match write(&grammar, &out) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::{HashMap, HashSet};
+
+use super::*;
+use crate::meta::*;
+
+// Works out a Rust type for every rule from its named captures and renders
+// the types together with their `FromToken` impls.
+pub fn generate_ast(grammar: &str) -> Result<String, GrammarError> {
+    Ok(AstGen::new(&read_rule_set(grammar)?).render())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Ty {
+    Text,
+    Named(String),
+    Boxed(String),
+    Vec(Box<Ty>),
+    Option(Box<Ty>),
+}
+
+impl Ty {
+    // Std types get full paths, since a rule named `string` or `vec` becomes
+    // a type of the same name.
+    fn render(&self) -> String {
+        match self {
+            Ty::Text => "::std::string::String".to_string(),
+            Ty::Named(name) => name.clone(),
+            Ty::Boxed(name) => format!("::std::boxed::Box<{name}>"),
+            Ty::Vec(ty) => format!("::std::vec::Vec<{}>", ty.render()),
+            Ty::Option(ty) => format!("::core::option::Option<{}>", ty.render()),
+        }
+    }
+}
+
+enum Item {
+    Alias(String, Ty),
+    Struct(String, Vec<Field>),
+    Enum(String, Vec<(String, String, Option<Ty>)>),
+}
+
+struct Field {
+    name: String,
+    child: String,
+    ty: Ty,
+    repeated: bool,
+}
+
+struct AstGen<'g> {
+    rules: Vec<&'g RuleDef>,
+    types: HashMap<&'g str, String>,
+    reach: HashMap<&'g str, HashSet<&'g str>>,
+    defs: HashMap<&'g str, &'g SeqDef>,
+    taken: HashSet<String>,
+    items: Vec<Option<Item>>,
+}
+
+impl<'g> AstGen<'g> {
+    fn new(rules: &'g [RuleDef]) -> Self {
+        let mut gen = Self {
+            rules: rules.iter().collect(),
+            types: HashMap::new(),
+            reach: HashMap::new(),
+            defs: rules
+                .iter()
+                .map(|rule| (rule.name.as_str(), &rule.seq))
+                .collect(),
+            taken: HashSet::new(),
+            items: vec![],
+        };
+        for rule in rules {
+            let name = gen.unique(&type_name(&rule.name));
+            gen.types.insert(&rule.name, name);
+        }
+        let direct: HashMap<&str, Vec<&str>> = rules
+            .iter()
+            .map(|rule| (rule.name.as_str(), rule.seq.refs()))
+            .collect();
+        for rule in rules {
+            let mut seen = HashSet::new();
+            let mut stack = direct[rule.name.as_str()].clone();
+            while let Some(next) = stack.pop() {
+                if seen.insert(next) {
+                    stack.extend(direct.get(next).into_iter().flatten());
+                }
+            }
+            gen.reach.insert(&rule.name, seen);
+        }
+        gen
+    }
+
+    fn unique(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut n = 2;
+        while !self.taken.insert(candidate.clone()) {
+            candidate = format!("{name}{n}");
+            n += 1;
+        }
+        candidate
+    }
+
+    fn render(mut self) -> String {
+        for rule in self.rules.clone() {
+            let name = self.types[rule.name.as_str()].clone();
+            let slot = self.items.len();
+            self.items.push(None);
+            let ty = self.ty_of(&rule.seq, &name, &rule.name, true);
+            if ty != Ty::Named(name.clone()) {
+                self.items[slot] = Some(Item::Alias(name, ty));
+            }
+        }
+        let mut out = "// Generated by tuckey from a grammar; do not edit.\n".to_string();
+        for item in self.items.iter().flatten() {
+            out += "\n";
+            out += &render_item(item);
+        }
+        out
+    }
+
+    // `top` is set for the whole body of a rule, whose type takes the rule's
+    // name; repetitions and options there name their element after the rule
+    // and the capture instead.
+    fn ty_of(&mut self, seq: &SeqDef, hint: &str, rule: &str, top: bool) -> Ty {
+        match seq {
+            SeqDef::Raw(_)
+            | SeqDef::Chars(_)
+            | SeqDef::Range(..)
+            | SeqDef::Except(_)
+            | SeqDef::Lookahead(..) => Ty::Text,
+            SeqDef::Ref(name) => match self.types.get(name.as_str()) {
+                Some(ty) if self.reach[name.as_str()].contains(rule) => Ty::Boxed(ty.clone()),
+                Some(ty) => Ty::Named(ty.clone()),
+                None => Ty::Text,
+            },
+            SeqDef::OneOrMore(seq, name)
+            | SeqDef::NoneOrMore(seq, name)
+            | SeqDef::Repeat(seq, name, ..) => {
+                if is_text(seq, &self.defs, &mut vec![]) {
+                    return Ty::Text;
+                }
+                // A `Vec` already puts its elements behind a pointer.
+                match self.ty_of(seq, &inner_hint(hint, name, top), rule, false) {
+                    Ty::Boxed(name) => Ty::Vec(Box::new(Ty::Named(name))),
+                    ty => Ty::Vec(Box::new(ty)),
+                }
+            }
+            // Kept separators share the list with the items, so such a list
+            // is only typed as its text.
+            SeqDef::Sep(seq, name, _, sep_name, ..) => {
+                if !sep_name.is_empty() || is_text(seq, &self.defs, &mut vec![]) {
+                    return Ty::Text;
+                }
+                match self.ty_of(seq, &inner_hint(hint, name, top), rule, false) {
+                    Ty::Boxed(name) => Ty::Vec(Box::new(Ty::Named(name))),
+                    ty => Ty::Vec(Box::new(ty)),
+                }
+            }
+            SeqDef::Opt(seq, name) => {
+                let ty = self.ty_of(seq, &inner_hint(hint, name, top), rule, false);
+                Ty::Option(Box::new(ty))
+            }
+            SeqDef::Recover(seq, _) => self.ty_of(seq, hint, rule, top),
+            SeqDef::Mult(seqs) | SeqDef::Cut(seqs, _) => self.struct_of(seqs, hint, rule, top),
+            SeqDef::Choose(seqs) => self.enum_of(seqs, hint, rule, top),
+        }
+    }
+
+    fn struct_of(&mut self, seqs: &[(SeqDef, String)], hint: &str, rule: &str, top: bool) -> Ty {
+        let mut labels: Vec<(&str, &SeqDef, usize)> = vec![];
+        for (seq, label) in seqs.iter().filter(|(_, label)| !label.is_empty()) {
+            match labels.iter_mut().find(|(l, ..)| l == label) {
+                Some((.., count)) => *count += 1,
+                None => labels.push((label, seq, 1)),
+            }
+        }
+        if labels.is_empty() {
+            return Ty::Text;
+        }
+        let name = if top {
+            hint.to_string()
+        } else {
+            self.unique(hint)
+        };
+        let slot = self.items.len();
+        self.items.push(None);
+        let fields = labels
+            .into_iter()
+            .map(|(label, seq, count)| Field {
+                name: field_name(label),
+                child: label.to_string(),
+                ty: self.ty_of(seq, &format!("{name}{}", type_name(label)), rule, false),
+                repeated: count > 1,
+            })
+            .collect();
+        self.items[slot] = Some(Item::Struct(name.clone(), fields));
+        Ty::Named(name)
+    }
+
+    fn enum_of(&mut self, seqs: &[(SeqDef, String)], hint: &str, rule: &str, top: bool) -> Ty {
+        if !enum_like(seqs) {
+            return Ty::Text;
+        }
+        let variants = seqs.iter().map(|(_, name)| type_name(name));
+        let name = if top {
+            hint.to_string()
+        } else {
+            self.unique(hint)
+        };
+        let slot = self.items.len();
+        self.items.push(None);
+        let variants = seqs
+            .iter()
+            .zip(variants)
+            .map(|((seq, child), variant)| {
+                let ty = self.ty_of(seq, &format!("{name}{variant}"), rule, false);
+                (
+                    variant,
+                    child.clone(),
+                    Some(ty).filter(|ty| *ty != Ty::Text),
+                )
+            })
+            .collect();
+        self.items[slot] = Some(Item::Enum(name.clone(), variants));
+        Ty::Named(name)
+    }
+}
+
+// Whether a sequence carries no named captures, so that its content is all
+// there is to keep. Rules on `stack` are being checked already.
+fn is_text<'g>(
+    seq: &'g SeqDef,
+    defs: &HashMap<&str, &'g SeqDef>,
+    stack: &mut Vec<&'g str>,
+) -> bool {
+    match seq {
+        SeqDef::Raw(_)
+        | SeqDef::Chars(_)
+        | SeqDef::Range(..)
+        | SeqDef::Except(_)
+        | SeqDef::Lookahead(..) => true,
+        SeqDef::Ref(name) => match defs.get(name.as_str()) {
+            None => true,
+            Some(_) if stack.contains(&name.as_str()) => false,
+            Some(def) => {
+                stack.push(name);
+                let text = is_text(def, defs, stack);
+                stack.pop();
+                text
+            }
+        },
+        SeqDef::OneOrMore(seq, _) | SeqDef::NoneOrMore(seq, _) | SeqDef::Repeat(seq, ..) => {
+            is_text(seq, defs, stack)
+        }
+        SeqDef::Recover(seq, _) | SeqDef::Sep(seq, ..) => is_text(seq, defs, stack),
+        SeqDef::Opt(..) => false,
+        SeqDef::Mult(seqs) | SeqDef::Cut(seqs, _) => seqs.iter().all(|(_, label)| label.is_empty()),
+        SeqDef::Choose(seqs) => !enum_like(seqs),
+    }
+}
+
+fn enum_like(seqs: &[(SeqDef, String)]) -> bool {
+    let variants: Vec<String> = seqs.iter().map(|(_, name)| type_name(name)).collect();
+    let distinct: HashSet<&String> = variants.iter().collect();
+    variants.iter().all(|v| !v.is_empty()) && distinct.len() == variants.len()
+}
+
+fn inner_hint(hint: &str, name: &str, top: bool) -> String {
+    match (top, type_name(name)) {
+        (false, _) => hint.to_string(),
+        (true, name) if name.is_empty() => format!("{hint}Item"),
+        (true, name) => format!("{hint}{name}"),
+    }
+}
+
+fn render_item(item: &Item) -> String {
+    let header = "#[derive(Debug, Clone, PartialEq)]\n";
+    let impl_header = |name: &str| {
+        format!(
+            "impl<'a> ::tuckey::ast::FromToken<'a> for {name} {{\n    \
+             fn from_token(\n        \
+             token: &::tuckey::token::Token<'a>,\n    \
+             ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {{\n"
+        )
+    };
+    match item {
+        Item::Alias(name, ty) => format!("pub type {name} = {};\n", ty.render()),
+        Item::Struct(name, fields) => {
+            let mut out = format!("{header}pub struct {name} {{\n");
+            for field in fields {
+                let ty = match field.repeated {
+                    true => format!("::std::vec::Vec<{}>", field.ty.render()),
+                    false => field.ty.render(),
+                };
+                out += &format!("    pub {}: {ty},\n", field.name);
+            }
+            out += "}\n\n";
+            out += &impl_header(name);
+            out += "        Ok(Self {\n";
+            for field in fields {
+                let helper = match field.repeated {
+                    true => "from_children",
+                    false => "from_child",
+                };
+                out += &format!(
+                    "            {}: ::tuckey::ast::{helper}(token, {})?,\n",
+                    field.name,
+                    rust_string(&field.child)
+                );
+            }
+            out + "        })\n    }\n}\n"
+        }
+        Item::Enum(name, variants) => {
+            let mut out = format!("{header}pub enum {name} {{\n");
+            for (variant, _, ty) in variants {
+                match ty {
+                    Some(ty) => out += &format!("    {variant}({}),\n", ty.render()),
+                    None => out += &format!("    {variant},\n"),
+                }
+            }
+            out += "}\n\n";
+            out += &impl_header(name);
+            out += "        match &token.data {\n";
+            out += "            ::tuckey::token::TokenData::Branch(children) if children.len() == 1 => {\n";
+            match variants.iter().any(|(.., ty)| ty.is_some()) {
+                true => out += "                let (name, child) = &children[0];\n",
+                false => out += "                let (name, _) = &children[0];\n",
+            }
+            out += "                match name.as_str() {\n";
+            for (variant, child, ty) in variants {
+                let value = match ty {
+                    Some(_) => format!(
+                        "Ok({name}::{variant}(::tuckey::ast::FromToken::from_token(child)?))"
+                    ),
+                    None => format!("Ok({name}::{variant})"),
+                };
+                out += &format!("                    {} => {value},\n", rust_string(child));
+            }
+            let expected = variants
+                .iter()
+                .map(|(_, child, _)| format!("`{child}`"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let error = format!(
+                "Err(::tuckey::ast::AstError::at({}, token))",
+                rust_string(&format!("expected one of {expected}"))
+            );
+            out += &format!("                    _ => {error},\n");
+            out += "                }\n";
+            out += "            }\n";
+            out += &format!("            _ => {error},\n");
+            out + "        }\n    }\n}\n"
+        }
+    }
+}
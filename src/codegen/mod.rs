@@ -0,0 +1,136 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
+
+use crate::meta::GrammarError;
+
+mod ast;
+mod parser;
+pub use ast::*;
+pub use parser::*;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug)]
+pub enum BuildError {
+    Io(PathBuf, io::Error),
+    Grammar(PathBuf, GrammarError),
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::Io(path, err) => write!(f, "{}: {err}", path.display()),
+            BuildError::Grammar(path, err) => write!(f, "{}:{err}", path.display()),
+        }
+    }
+}
+
+impl Error for BuildError {}
+
+// For build scripts: reads a `.tuckey` grammar, writes the generated AST
+// types to `$OUT_DIR/<stem>_ast.rs` and returns that path, ready for
+// `include!(concat!(env!("OUT_DIR"), "/<stem>_ast.rs"))`.
+pub fn build_ast(grammar: impl AsRef<Path>) -> Result<PathBuf, BuildError> {
+    build(grammar.as_ref(), "ast", generate_ast)
+}
+
+// Like `build_ast`, but writes a parser to `$OUT_DIR/<stem>_parser.rs`.
+pub fn build_parser(grammar: impl AsRef<Path>) -> Result<PathBuf, BuildError> {
+    build(grammar.as_ref(), "parser", generate_parser)
+}
+
+pub fn write_ast(grammar: &Path, out: &Path) -> Result<(), BuildError> {
+    write_generated(grammar, out, generate_ast)
+}
+
+pub fn write_parser(grammar: &Path, out: &Path) -> Result<(), BuildError> {
+    write_generated(grammar, out, generate_parser)
+}
+
+type Generator = fn(&str) -> Result<String, GrammarError>;
+
+fn build(grammar: &Path, suffix: &str, generate: Generator) -> Result<PathBuf, BuildError> {
+    println!("cargo:rerun-if-changed={}", grammar.display());
+    let out_dir = env::var_os("OUT_DIR").map(PathBuf::from).ok_or_else(|| {
+        BuildError::Io(
+            grammar.to_path_buf(),
+            io::Error::other("OUT_DIR is not set; call this from a build script"),
+        )
+    })?;
+    let stem = grammar
+        .file_stem()
+        .map_or("grammar".into(), |stem| stem.to_string_lossy());
+    let out = out_dir.join(format!("{stem}_{suffix}.rs"));
+    write_generated(grammar, &out, generate)?;
+    Ok(out)
+}
+
+fn write_generated(grammar: &Path, out: &Path, generate: Generator) -> Result<(), BuildError> {
+    let text = fs::read_to_string(grammar).map_err(|e| BuildError::Io(grammar.to_path_buf(), e))?;
+    let code = generate(&text).map_err(|e| BuildError::Grammar(grammar.to_path_buf(), e))?;
+    fs::write(out, code).map_err(|e| BuildError::Io(out.to_path_buf(), e))
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+// `multExpr` and `rhs's` become `MultExpr` and `RhsS`; names with nothing
+// usable in them come back empty.
+fn type_name(name: &str) -> String {
+    let mut out = String::new();
+    let mut upper = true;
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            if upper {
+                out.extend(c.to_uppercase());
+            } else {
+                out.push(c);
+            }
+            upper = false;
+        } else {
+            upper = true;
+        }
+    }
+    if out.starts_with(|c: char| c.is_ascii_digit()) || out == "Self" {
+        out.insert(0, '_');
+    }
+    out
+}
+
+// `firstName` and `rhs's` become `first_name` and `rhs_s`.
+fn field_name(name: &str) -> String {
+    let mut out = String::new();
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else if c.is_ascii_alphanumeric() {
+            out.push(c);
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    match out.as_str() {
+        "self" | "super" | "crate" => out.push('_'),
+        name if KEYWORDS.contains(&name) => out.insert_str(0, "r#"),
+        _ => {}
+    }
+    out
+}
+
+fn rust_string(text: &str) -> String {
+    format!("{text:?}")
+}
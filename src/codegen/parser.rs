@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::*;
+use crate::meta::*;
+
+// Turns a grammar into one plain function per rule, with the signature of
+// `sequence::RuleFn`, that builds the same tokens as the interpreted rules.
+// Nested sequences become private helper functions; `RULES` lists the rules
+// by name.
+pub fn generate_parser(grammar: &str) -> Result<String, GrammarError> {
+    Ok(ParserGen::new(&read_rule_set(grammar)?).render())
+}
+
+struct ParserGen<'g> {
+    rules: Vec<&'g RuleDef>,
+    recursive: HashSet<&'g str>,
+    fns: HashMap<&'g str, String>,
+    taken: HashSet<String>,
+    helpers: usize,
+    out: Vec<String>,
+}
+
+// Code matching a sequence, and whether it refers to the context and the
+// position it was given, so that closures around it name only what it uses.
+struct Expr {
+    code: String,
+    ctx: bool,
+    at: bool,
+}
+
+impl Expr {
+    fn new(code: String) -> Self {
+        Self {
+            code,
+            ctx: true,
+            at: true,
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+// Names the generated code uses for its own parameters and locals, which
+// rule functions must not shadow.
+const RESERVED: &[&str] = &[
+    "tokens", "pos", "ctx", "len", "children", "matched", "at", "m",
+];
+
+const SIGNATURE: &str = "<'a>(\n    \
+    tokens: &[Token<'a>],\n    \
+    pos: usize,\n    \
+    ctx: &mut ParseContext<'a>,\n) -> Option<TokenMatch<'a>>";
+
+impl<'g> ParserGen<'g> {
+    fn new(all: &'g [RuleDef]) -> Self {
+        // A later rule with the same name replaces an earlier one, as in
+        // `eval_rule_set`.
+        let rules: Vec<&RuleDef> = all
+            .iter()
+            .enumerate()
+            .filter(|(i, rule)| !all[i + 1..].iter().any(|later| later.name == rule.name))
+            .map(|(_, rule)| rule)
+            .collect();
+        let mut gen = Self {
+            rules: rules.clone(),
+            recursive: left_recursive_rules(all),
+            fns: HashMap::new(),
+            taken: RESERVED.iter().map(|name| name.to_string()).collect(),
+            helpers: 0,
+            out: vec![],
+        };
+        for rule in rules {
+            let name = gen.unique(&field_name(&rule.name));
+            gen.fns.insert(&rule.name, name);
+        }
+        gen
+    }
+
+    fn unique(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut n = 2;
+        while !self.taken.insert(candidate.clone()) {
+            candidate = format!("{name}_{n}");
+            n += 1;
+        }
+        candidate
+    }
+
+    fn render(mut self) -> String {
+        let mut out = "// Generated by tuckey from a grammar; do not edit.\n\n".to_string();
+        out += "use ::tuckey::sequence::{ParseContext, RuleFn, TokenMatch};\n";
+        out += "use ::tuckey::token::Token;\n\n";
+        out += "pub const RULES: &[(&str, RuleFn)] = &[\n";
+        for rule in &self.rules {
+            out += &format!(
+                "    ({}, {}),\n",
+                rust_string(&rule.name),
+                self.fns[rule.name.as_str()]
+            );
+        }
+        out += "];\n";
+        for rule in self.rules.clone() {
+            let name = self.fns[rule.name.as_str()].clone();
+            let slot = self.out.len();
+            self.out.push(String::new());
+            self.helpers = 0;
+            let body = self.expr(&rule.seq, &name, "pos");
+            // Only left-recursive rules need seeds.
+            let call = match self.recursive.contains(rule.name.as_str()) {
+                true => "call_rule",
+                false => "call_plain_rule",
+            };
+            self.out[slot] = format!(
+                "pub fn {name}{SIGNATURE} {{\n    ctx.{call}({}, pos, |ctx| {body})\n}}\n",
+                rust_string(&rule.name)
+            );
+        }
+        for item in self.out {
+            out += "\n";
+            out += &item;
+        }
+        out
+    }
+
+    // An expression matching `seq` at `at`, adding helper functions for
+    // anything that needs statements.
+    fn expr(&mut self, seq: &SeqDef, owner: &str, at: &str) -> Expr {
+        match seq {
+            SeqDef::Raw(text) => Expr::new(format!(
+                "::tuckey::sequence::match_raw({}, tokens, {at}, ctx)",
+                rust_string(text)
+            )),
+            SeqDef::Range(start, end) => Expr::new(format!(
+                "::tuckey::sequence::match_range({start}, {end}, tokens, {at}, ctx)"
+            )),
+            SeqDef::Ref(name) => match (self.fns.get(name.as_str()), name.as_str()) {
+                (Some(name), _) => Expr::new(format!("{name}(tokens, {at}, ctx)")),
+                (None, "ws") => Expr {
+                    code: format!(
+                        "Some(::tuckey::sequence::rule_match(\"ws\", ::tuckey::sequence::match_whitespace(tokens, {at})))"
+                    ),
+                    ctx: false,
+                    at: true,
+                },
+                (None, "any") => Expr::new(format!(
+                    "ctx.call_plain_rule(\"any\", {at}, |ctx| ::tuckey::sequence::match_any(tokens, {at}, ctx))"
+                )),
+                (None, "_") => Expr {
+                    code: "Some(::tuckey::sequence::rule_match(\"_\", ::tuckey::sequence::match_nil()))"
+                        .to_string(),
+                    ctx: false,
+                    at: false,
+                },
+                (None, name) => Expr::new(format!(
+                    "{{ ctx.fail({at}, ::tuckey::sequence::Expected::Rule({}.to_string())); None::<TokenMatch<'a>> }}",
+                    rust_string(name)
+                )),
+            },
+            _ => {
+                self.helpers += 1;
+                let name = self.unique(&format!("{owner}_{}", self.helpers));
+                let slot = self.out.len();
+                self.out.push(String::new());
+                let body = self.body(seq, owner);
+                self.out[slot] = format!("fn {name}{SIGNATURE} {{\n{body}}}\n");
+                Expr::new(format!("{name}(tokens, {at}, ctx)"))
+            }
+        }
+    }
+
+    fn body(&mut self, seq: &SeqDef, owner: &str) -> String {
+        let mut out = String::new();
+        match seq {
+            SeqDef::Mult(seqs) | SeqDef::Cut(seqs, _) => {
+                let cut = match seq {
+                    SeqDef::Cut(_, cut) => *cut,
+                    _ => seqs.len(),
+                };
+                out += "    let mut len = 0;\n";
+                out += &format!(
+                    "    let mut children = Vec::with_capacity({});\n",
+                    seqs.len()
+                );
+                let mut indent = "    ";
+                for (i, (seq, name)) in seqs.iter().enumerate() {
+                    if i == cut {
+                        out += "    ctx.commit(tokens, |ctx| {\n";
+                        indent = "        ";
+                    }
+                    let call = self.expr(seq, owner, "pos + len");
+                    out += &format!("{indent}let matched = {call}?;\n");
+                    out += &format!(
+                        "{indent}children.push(({}.to_string(), matched.new_token));\n",
+                        rust_string(name)
+                    );
+                    out += &format!("{indent}len += matched.len;\n");
+                }
+                if cut < seqs.len() {
+                    out += "        Some(())\n    })?;\n";
+                }
+                out += "    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))\n";
+            }
+            SeqDef::Choose(seqs) => {
+                for (alt, (seq, name)) in seqs.iter().enumerate() {
+                    let call = self.expr(seq, owner, "pos");
+                    out += &format!("    if let Some(matched) = {call} {{\n");
+                    out += &format!(
+                        "        return Some(::tuckey::sequence::choose_match({alt}, {}, matched));\n",
+                        rust_string(name)
+                    );
+                    out += "    }\n";
+                    if alt + 1 < seqs.len() {
+                        out += "    if ctx.cut_failed() {\n        return None;\n    }\n";
+                    }
+                }
+                out += "    None\n";
+            }
+            SeqDef::Chars(chars) => {
+                for (alt, c) in chars.graphemes(true).enumerate() {
+                    out += &format!(
+                        "    if let Some(matched) = ::tuckey::sequence::match_raw({}, tokens, pos, ctx) {{\n",
+                        rust_string(c)
+                    );
+                    out += &format!(
+                        "        return Some(::tuckey::sequence::choose_match({alt}, {}, matched));\n",
+                        rust_string(c)
+                    );
+                    out += "    }\n";
+                }
+                out += "    None\n";
+            }
+            SeqDef::OneOrMore(inner, name) | SeqDef::NoneOrMore(inner, name) => {
+                let call = self.expr(inner, owner, "pos + len");
+                out += "    let mut len = 0;\n";
+                out += "    let mut children = vec![];\n";
+                out += &format!("    while let Some(matched) = {call} {{\n");
+                out += &format!(
+                    "        children.push(({}.to_string(), matched.new_token));\n",
+                    rust_string(name)
+                );
+                out += "        if matched.len == 0 {\n            break;\n        }\n";
+                out += "        len += matched.len;\n";
+                out += "    }\n";
+                match seq {
+                    SeqDef::OneOrMore(..) => {
+                        out +=
+                            "    if children.is_empty() || ctx.cut_failed() {\n        return None;\n    }\n"
+                    }
+                    _ => out += "    if ctx.cut_failed() {\n        return None;\n    }\n",
+                }
+                out += "    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))\n";
+            }
+            SeqDef::Repeat(inner, name, min, max) => {
+                let call = self.expr(inner, owner, "at");
+                out += "    ::tuckey::sequence::match_repeat(\n";
+                out += "        tokens,\n        pos,\n        ctx,\n";
+                out += &format!("        {},\n", rust_string(name));
+                out += &format!("        {min},\n        {max:?},\n");
+                out += &format!("        {},\n", closure(&call));
+                out += "    )\n";
+            }
+            SeqDef::Sep(inner, name, sep, sep_name, min, trailing) => {
+                let item = self.expr(inner, owner, "at");
+                let sep = self.expr(sep, owner, "at");
+                let sep_name = match sep_name.as_str() {
+                    "" => "None".to_string(),
+                    name => format!("Some({})", rust_string(name)),
+                };
+                out += "    ::tuckey::sequence::match_separated(\n";
+                out += "        tokens,\n        pos,\n        ctx,\n";
+                out += &format!("        {},\n", rust_string(name));
+                out += &format!("        {sep_name},\n");
+                out += &format!("        {min},\n        {trailing},\n");
+                out += &format!("        {},\n", closure(&item));
+                out += &format!("        {},\n", closure(&sep));
+                out += "    )\n";
+            }
+            SeqDef::Opt(inner, name) => {
+                let call = self.expr(inner, owner, "pos");
+                out += &format!("    match {call} {{\n");
+                out += &format!(
+                    "        Some(matched) => Some(::tuckey::sequence::branch_at(\n            tokens,\n            pos,\n            matched.len,\n            vec![({}.to_string(), matched.new_token)],\n        )),\n",
+                    rust_string(name)
+                );
+                out += "        None if ctx.cut_failed() => None,\n";
+                out += "        None => Some(::tuckey::sequence::branch_at(tokens, pos, 0, vec![])),\n";
+                out += "    }\n";
+            }
+            SeqDef::Except(seq) => {
+                let call = self.expr(seq, owner, "pos");
+                out += "    ::tuckey::sequence::match_except(tokens, pos, ctx, |ctx| {\n";
+                out += &format!("        {call}.is_some()\n");
+                out += "    })\n";
+            }
+            SeqDef::Lookahead(seq, negate) => {
+                let call = self.expr(seq, owner, "pos");
+                out += &format!(
+                    "    ::tuckey::sequence::match_lookahead(tokens, pos, ctx, {negate}, |ctx| {{\n"
+                );
+                out += &format!("        {call}.is_some()\n");
+                out += "    })\n";
+            }
+            SeqDef::Recover(seq, sync) => {
+                let call = self.expr(seq, owner, "pos");
+                let sync = match sync {
+                    Some(sync) => {
+                        let sync = self.expr(sync, owner, "at");
+                        closure(&Expr {
+                            code: format!("{sync}.map(|m| m.len)"),
+                            ..sync
+                        })
+                    }
+                    None => "|_, at| ::tuckey::sequence::match_line_end(tokens, at)".to_string(),
+                };
+                out += "    ::tuckey::sequence::match_recover(\n";
+                out += "        tokens,\n        pos,\n        ctx,\n";
+                out += &format!("        |ctx| {call},\n");
+                out += &format!("        {sync},\n");
+                out += "    )\n";
+            }
+            SeqDef::Raw(_) | SeqDef::Range(..) | SeqDef::Ref(_) => {
+                out += &format!("    {}\n", self.expr(seq, owner, "pos"));
+            }
+        }
+        out
+    }
+}
+
+// A closure over `call` taking the context and position. Builtins such as `_`
+// leave them unused.
+fn closure(call: &Expr) -> String {
+    let ctx = if call.ctx { "ctx" } else { "_" };
+    let at = if call.at { "at" } else { "_" };
+    format!("|{ctx}, {at}| {call}")
+}
@@ -0,0 +1,110 @@
+use super::*;
+
+#[test]
+pub fn calc_ast_test() {
+    let code = generate_ast(include_str!("../tests/calc.tuckey")).unwrap();
+    assert!(
+        code == include_str!("../tests/calc_ast.rs"),
+        "src/tests/calc_ast.rs is out of date with calc.tuckey; regenerate it with:\n{code}"
+    );
+}
+
+#[test]
+pub fn names_ast_test() {
+    let code = generate_ast(include_str!("../tests/names.tuckey")).unwrap();
+    assert!(
+        code == include_str!("../tests/names_ast.rs"),
+        "src/tests/names_ast.rs is out of date with names.tuckey; regenerate it with:\n{code}"
+    );
+}
+
+#[test]
+pub fn calc_parser_test() {
+    let code = generate_parser(include_str!("../tests/calc.tuckey")).unwrap();
+    assert!(
+        code == include_str!("../tests/calc_parser.rs"),
+        "src/tests/calc_parser.rs is out of date with calc.tuckey; regenerate it with:\n{code}"
+    );
+}
+
+#[test]
+pub fn features_parser_test() {
+    let code = generate_parser(include_str!("../tests/features.tuckey")).unwrap();
+    assert!(
+        code == include_str!("../tests/features_parser.rs"),
+        "src/tests/features_parser.rs is out of date with features.tuckey; regenerate it with:\n{code}"
+    );
+}
+
+#[test]
+pub fn left_parser_test() {
+    let code = generate_parser(include_str!("../tests/left.tuckey")).unwrap();
+    assert!(
+        code == include_str!("../tests/left_parser.rs"),
+        "src/tests/left_parser.rs is out of date with left.tuckey; regenerate it with:\n{code}"
+    );
+}
+
+#[test]
+pub fn shape_test() {
+    let code = generate_ast(
+        "
+    digit = 0..9
+    number = digit+
+    name = a..z+
+    pair = number:n & ',' & number:n
+    binding = {let} + name:type + (':' + name:name)?:hint + '=' + value:value
+    value = number.num | name.var | ('(' & list:list & ')').list
+    list = (value:item & _).item*
+    ",
+    )
+    .unwrap();
+    for expected in [
+        "pub type Number = ::std::string::String;",
+        "pub struct Pair {\n    pub n: ::std::vec::Vec<Number>,\n}",
+        "n: ::tuckey::ast::from_children(token, \"n\")?",
+        "pub r#type: Name,",
+        "pub hint: ::core::option::Option<BindingHint>,",
+        "pub struct BindingHint {\n    pub name: Name,\n}",
+        "pub enum Value {\n    Num(Number),\n    Var(Name),\n    List(ValueList),\n}",
+        "pub struct ValueList {\n    pub list: ::std::boxed::Box<List>,\n}",
+        "pub type List = ::std::vec::Vec<ListItem>;",
+        "pub struct ListItem {\n    pub item: ::std::boxed::Box<Value>,\n}",
+    ] {
+        assert!(code.contains(expected), "missing {expected:?} in\n{code}");
+    }
+}
+
+#[test]
+pub fn closure_test() {
+    // Closures only name the context and position when their body uses them.
+    let code = generate_parser("main = 'a' % ws & _{1,} & 'b'{2}").unwrap();
+    for expected in [
+        "|ctx, at| ::tuckey::sequence::match_raw(\"a\", tokens, at, ctx)",
+        "|_, at| Some(::tuckey::sequence::rule_match(\"ws\"",
+        "|_, _| Some(::tuckey::sequence::rule_match(\"_\"",
+        "|ctx, at| ::tuckey::sequence::match_raw(\"b\", tokens, at, ctx)",
+    ] {
+        assert!(code.contains(expected), "missing {expected:?} in\n{code}");
+    }
+}
+
+#[test]
+pub fn name_test() {
+    assert_eq!(type_name("multExpr"), "MultExpr");
+    assert_eq!(type_name("rhs's"), "RhsS");
+    assert_eq!(type_name("2d"), "_2d");
+    assert_eq!(field_name("firstName"), "first_name");
+    assert_eq!(field_name("rhs's"), "rhs_s");
+    assert_eq!(field_name("match"), "r#match");
+    assert_eq!(field_name("self"), "self_");
+}
+
+#[test]
+pub fn error_test() {
+    let err = generate_ast("main 'a'").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "1:6: expected `=`, found `'` (in rule `main`)"
+    );
+}
@@ -1,4 +1,6 @@
+use crate::source::*;
 use crate::token::*;
+use unicode_segmentation::UnicodeSegmentation;
 
 pub struct Corpus<'a> {
     pub tokens: Vec<Token<'a>>,
@@ -6,11 +8,40 @@ pub struct Corpus<'a> {
 
 impl<'a> Corpus<'a> {
     pub fn make(text: &'a str) -> Self {
+        Self::make_in(text, FileId::default())
+    }
+
+    pub fn make_in(text: &'a str, file: FileId) -> Self {
+        Corpus {
+            tokens: text
+                .char_indices()
+                .map(|(i, c)| Token {
+                    source: text,
+                    file,
+                    rule: None,
+                    alt: None,
+                    value: None,
+                    data: TokenData::Leaf(i..i + c.len_utf8()),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn make_graphemes(text: &'a str) -> Self {
+        Self::make_graphemes_in(text, FileId::default())
+    }
+
+    pub fn make_graphemes_in(text: &'a str, file: FileId) -> Self {
         Corpus {
-            tokens: (0..text.len())
-                .map(|i| Token {
+            tokens: text
+                .grapheme_indices(true)
+                .map(|(i, g)| Token {
                     source: text,
-                    data: TokenData::Leaf(i..i + 1),
+                    file,
+                    rule: None,
+                    alt: None,
+                    value: None,
+                    data: TokenData::Leaf(i..i + g.len()),
                 })
                 .collect(),
         }
@@ -0,0 +1,477 @@
+use core::ops::Range;
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::de::value::StrDeserializer;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::Deserialize;
+
+use crate::token::*;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeError {
+    pub message: String,
+    pub span: Option<Range<usize>>,
+}
+
+impl DeError {
+    fn at(message: impl Into<String>, token: &Token<'_>) -> Self {
+        Self {
+            message: message.into(),
+            span: token.content_range(),
+        }
+    }
+}
+
+impl Display for DeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{} at {}..{}", self.message, span.start, span.end),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for DeError {}
+
+impl de::Error for DeError {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+            span: None,
+        }
+    }
+}
+
+pub fn from_token<'de, T: Deserialize<'de>>(token: &Token<'de>) -> Result<T, DeError> {
+    T::deserialize(TokenDeserializer { token })
+}
+
+pub struct TokenDeserializer<'a, 'de> {
+    token: &'a Token<'de>,
+}
+
+impl<'a, 'de> TokenDeserializer<'a, 'de> {
+    pub fn new(token: &'a Token<'de>) -> Self {
+        Self { token }
+    }
+
+    fn content(&self) -> &'de str {
+        self.token
+            .content_range()
+            .map_or("", |range| &self.token.source[range])
+    }
+
+    fn parse<T: FromStr>(&self, what: &str) -> Result<T, DeError> {
+        self.content().trim().parse().map_err(|_| {
+            DeError::at(
+                format!("expected {what}, found `{}`", self.content()),
+                self.token,
+            )
+        })
+    }
+
+    fn children(&self) -> &'a [(String, Token<'de>)] {
+        match &self.token.data {
+            TokenData::Leaf(_) | TokenData::Error(..) => &[],
+            TokenData::Branch(children) => children,
+        }
+    }
+
+    // Wrappers left by `OptSeq`, `ChooseSeq` or an unnamed capture hold a
+    // single branch; look through them until one of `fields` shows up.
+    fn find_fields(&self, fields: &[&str]) -> &'a Token<'de> {
+        let mut token = self.token;
+        while let TokenData::Branch(children) = &token.data {
+            match &children[..] {
+                [(name, child)]
+                    if !fields.contains(&name.as_str())
+                        && matches!(child.data, TokenData::Branch(_)) =>
+                {
+                    token = child
+                }
+                _ => break,
+            }
+        }
+        token
+    }
+}
+
+macro_rules! parse_primitive {
+    ($($method:ident => $visit:ident : $what:literal,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+                visitor.$visit(self.parse($what)?)
+            }
+        )*
+    };
+}
+
+impl<'a, 'de> de::Deserializer<'de> for TokenDeserializer<'a, 'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match &self.token.data {
+            TokenData::Leaf(_) => visitor.visit_borrowed_str(self.content()),
+            TokenData::Branch(_) => self.deserialize_map(visitor),
+            TokenData::Error(_, error) => Err(DeError::at(error.message(), self.token)),
+        }
+    }
+
+    parse_primitive! {
+        deserialize_bool => visit_bool: "a boolean",
+        deserialize_i8 => visit_i8: "an integer",
+        deserialize_i16 => visit_i16: "an integer",
+        deserialize_i32 => visit_i32: "an integer",
+        deserialize_i64 => visit_i64: "an integer",
+        deserialize_i128 => visit_i128: "an integer",
+        deserialize_u8 => visit_u8: "an integer",
+        deserialize_u16 => visit_u16: "an integer",
+        deserialize_u32 => visit_u32: "an integer",
+        deserialize_u64 => visit_u64: "an integer",
+        deserialize_u128 => visit_u128: "an integer",
+        deserialize_f32 => visit_f32: "a number",
+        deserialize_f64 => visit_f64: "a number",
+        deserialize_char => visit_char: "a single character",
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_borrowed_str(self.content())
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_borrowed_str(self.content())
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_borrowed_bytes(self.content().as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match &self.token.data {
+            TokenData::Branch(children) if children.is_empty() => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let items = match &self.token.data {
+            TokenData::Leaf(_) | TokenData::Error(..) => vec![self.token],
+            TokenData::Branch(children) => children.iter().map(|(_, t)| t).collect(),
+        };
+        visitor.visit_seq(Seq {
+            items: items.into_iter(),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: usize,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        let mut entries: Vec<(&'a str, Vec<&'a Token<'de>>)> = vec![];
+        for (name, child) in self.children() {
+            match entries.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, tokens)) => tokens.push(child),
+                None => entries.push((name, vec![child])),
+            }
+        }
+        visitor.visit_map(Fields {
+            entries: entries.into_iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let token = self.find_fields(fields);
+        let entries = fields
+            .iter()
+            .map(|field| {
+                let mut tokens = vec![];
+                collect_captures(token, field, &mut tokens);
+                (*field, tokens)
+            })
+            .filter(|(_, tokens)| !tokens.is_empty())
+            .collect::<Vec<_>>();
+        visitor.visit_map(Fields {
+            entries: entries.into_iter(),
+            value: None,
+        })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        let mut token = self.token;
+        while let TokenData::Branch(children) = &token.data {
+            match &children[..] {
+                [(name, child)] if variants.contains(&name.as_str()) => {
+                    return visitor.visit_enum(Variant {
+                        name,
+                        token: Some(child),
+                    })
+                }
+                [(name, child)] if name.is_empty() => token = child,
+                _ => break,
+            }
+        }
+        let name = TokenDeserializer { token }.content();
+        if variants.contains(&name) {
+            visitor.visit_enum(Variant { name, token: None })
+        } else {
+            Err(DeError::at(
+                format!("expected one of {}, found `{name}`", variants.join(", ")),
+                token,
+            ))
+        }
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_unit()
+    }
+}
+
+// Children named `field`, including those inside unnamed groups of the same
+// rule such as `(',' & number:n)?`.
+fn collect_captures<'a, 'de>(token: &'a Token<'de>, field: &str, tokens: &mut Vec<&'a Token<'de>>) {
+    let TokenData::Branch(children) = &token.data else {
+        return;
+    };
+    for (name, child) in children.iter() {
+        if name == field {
+            tokens.push(child);
+        } else if name.is_empty() && child.rule.is_none() {
+            collect_captures(child, field, tokens);
+        }
+    }
+}
+
+// The match of a labelled `x*` or `x+`, rather than a single item: a branch
+// no rule produced, whose children (if any) all share one name.
+fn is_repetition(token: &Token<'_>) -> bool {
+    match &token.data {
+        TokenData::Branch(children) => {
+            token.rule.is_none() && children.windows(2).all(|pair| pair[0].0 == pair[1].0)
+        }
+        TokenData::Leaf(_) | TokenData::Error(..) => false,
+    }
+}
+
+struct Seq<'a, 'de> {
+    items: std::vec::IntoIter<&'a Token<'de>>,
+}
+
+impl<'de> de::SeqAccess<'de> for Seq<'_, 'de> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeError> {
+        self.items
+            .next()
+            .map(|token| seed.deserialize(TokenDeserializer { token }))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct Fields<'a, 'de> {
+    entries: std::vec::IntoIter<(&'a str, Vec<&'a Token<'de>>)>,
+    value: Option<Vec<&'a Token<'de>>>,
+}
+
+impl<'de> de::MapAccess<'de> for Fields<'_, 'de> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        let Some((name, tokens)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(tokens);
+        let key: StrDeserializer<'_, DeError> = name.into_deserializer();
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        let tokens = self.value.take().unwrap_or_default();
+        seed.deserialize(Captures { tokens })
+    }
+}
+
+// Every child captured under one name. Sequences take all of them, or the
+// items of a captured repetition; anything else needs exactly one.
+struct Captures<'a, 'de> {
+    tokens: Vec<&'a Token<'de>>,
+}
+
+impl<'a, 'de> Captures<'a, 'de> {
+    fn single(self) -> Result<TokenDeserializer<'a, 'de>, DeError> {
+        match self.tokens.as_slice() {
+            [token] => Ok(TokenDeserializer { token }),
+            [first, ..] => Err(DeError::at(
+                format!("expected one token, found {}", self.tokens.len()),
+                first,
+            )),
+            [] => Err(de::Error::custom("expected a token")),
+        }
+    }
+}
+
+macro_rules! forward_single {
+    ($($method:ident($($arg:ident: $ty:ty),*),)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, DeError> {
+                self.single()?.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Captures<'_, 'de> {
+    type Error = DeError;
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.tokens.as_slice() {
+            [token] if is_repetition(token) => TokenDeserializer { token }.deserialize_seq(visitor),
+            _ => visitor.visit_seq(Seq {
+                items: self.tokens.into_iter(),
+            }),
+        }
+    }
+
+    forward_single! {
+        deserialize_any(),
+        deserialize_bool(),
+        deserialize_i8(),
+        deserialize_i16(),
+        deserialize_i32(),
+        deserialize_i64(),
+        deserialize_i128(),
+        deserialize_u8(),
+        deserialize_u16(),
+        deserialize_u32(),
+        deserialize_u64(),
+        deserialize_u128(),
+        deserialize_f32(),
+        deserialize_f64(),
+        deserialize_char(),
+        deserialize_str(),
+        deserialize_string(),
+        deserialize_bytes(),
+        deserialize_byte_buf(),
+        deserialize_option(),
+        deserialize_unit(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+        deserialize_identifier(),
+        deserialize_ignored_any(),
+    }
+}
+
+struct Variant<'a, 'de> {
+    name: &'a str,
+    token: Option<&'a Token<'de>>,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for Variant<'a, 'de> {
+    type Error = DeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), DeError> {
+        let name: StrDeserializer<'_, DeError> = self.name.into_deserializer();
+        Ok((seed.deserialize(name)?, self))
+    }
+}
+
+impl<'a, 'de> Variant<'a, 'de> {
+    fn inner(&self) -> Result<TokenDeserializer<'a, 'de>, DeError> {
+        self.token
+            .map(|token| TokenDeserializer { token })
+            .ok_or_else(|| de::Error::custom(format!("variant `{}` has no content", self.name)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Variant<'_, 'de> {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, DeError> {
+        seed.deserialize(self.inner()?)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _: usize, visitor: V) -> Result<V::Value, DeError> {
+        de::Deserializer::deserialize_seq(self.inner()?, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        de::Deserializer::deserialize_struct(self.inner()?, "", fields, visitor)
+    }
+}
@@ -0,0 +1,159 @@
+use serde::Deserialize;
+
+use super::*;
+use crate::corpus::*;
+use crate::meta::*;
+use crate::sequence::*;
+
+fn rules() -> RefMap {
+    eval_rule_set(
+        "
+    digit = 0..9
+    letter = a..z
+    name = letter+
+    number = digit+
+    quoted = '\"' & [abc ]*:text & '\"'
+    value = number.num | name.var | quoted.str
+    type = ':' + name:name
+    binding = {let} + name:name + type?:ty + '=' + value:value & ';'
+    main = (_ + binding:binding).stmt*:stmts
+    pair = number:n & ',' & number:n & oper:oper
+    oper = [+-]
+    list = number:n & (',' & number:n)?
+    digits = '#' & digit*:n
+    ",
+    )
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Binding<'a> {
+    name: &'a str,
+    ty: Option<Type>,
+    value: Value,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Type {
+    name: String,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Value {
+    #[serde(rename = "num")]
+    Num(u32),
+    #[serde(rename = "var")]
+    Var(String),
+    #[serde(rename = "str")]
+    Str { text: String },
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Pair {
+    n: Vec<u8>,
+    oper: Oper,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Oper {
+    #[serde(rename = "+")]
+    Plus,
+    #[serde(rename = "-")]
+    Minus,
+}
+
+#[test]
+pub fn struct_test() {
+    let refs = rules();
+    let text = "let x: int = 42; let y = x;\nlet z = \"ab c\";";
+    let token = refs["main"]
+        .match_corpus_first(&Corpus::make(text), &refs)
+        .unwrap()
+        .new_token;
+    let stmts = token.get_children("stmt");
+    let bindings: Vec<Binding> = stmts
+        .iter()
+        .map(|stmt| from_token(&stmt.get_first_child("binding").unwrap()).unwrap())
+        .collect();
+    assert_eq!(
+        bindings,
+        vec![
+            Binding {
+                name: "x",
+                ty: Some(Type {
+                    name: "int".to_string()
+                }),
+                value: Value::Num(42),
+            },
+            Binding {
+                name: "y",
+                ty: None,
+                value: Value::Var("x".to_string()),
+            },
+            Binding {
+                name: "z",
+                ty: None,
+                value: Value::Str {
+                    text: "ab c".to_string()
+                },
+            },
+        ]
+    );
+
+    #[derive(Deserialize)]
+    struct Stmt<'a> {
+        #[serde(borrow)]
+        binding: Binding<'a>,
+    }
+    let stmts: Vec<Stmt> = from_token(&token).unwrap();
+    assert_eq!(stmts.len(), 3);
+    assert_eq!(stmts[2].binding.name, "z");
+}
+
+#[test]
+pub fn repeated_test() {
+    let refs = rules();
+    let corpus = Corpus::make("12,7-");
+    let token = refs["pair"]
+        .match_corpus_first(&corpus, &refs)
+        .unwrap()
+        .new_token;
+    let pair: Pair = from_token(&token).unwrap();
+    assert_eq!(
+        pair,
+        Pair {
+            n: vec![12, 7],
+            oper: Oper::Minus
+        }
+    );
+
+    let corpus = Corpus::make("999,7+");
+    let token = refs["pair"]
+        .match_corpus_first(&corpus, &refs)
+        .unwrap()
+        .new_token;
+    let err = from_token::<Pair>(&token).unwrap_err();
+    assert_eq!(err.message, "expected an integer, found `999`");
+    assert_eq!(err.span, Some(0..3));
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct List {
+    n: Vec<u16>,
+}
+
+#[test]
+pub fn single_capture_test() {
+    let refs = rules();
+    let list = |rule: &str, text| {
+        let token = refs[rule]
+            .match_corpus_first(&Corpus::make(text), &refs)
+            .unwrap()
+            .new_token;
+        from_token::<List>(&token).unwrap().n
+    };
+    assert_eq!(list("list", "12"), vec![12]);
+    assert_eq!(list("list", "12,7"), vec![12, 7]);
+    // A labelled repetition is a list of its items.
+    assert_eq!(list("digits", "#12"), vec![1, 2]);
+    assert_eq!(list("digits", "#"), vec![]);
+}
@@ -1,6 +1,17 @@
+extern crate self as tuckey;
+
+pub mod action;
+pub mod ast;
+pub mod codegen;
 pub mod corpus;
+pub mod de;
 pub mod meta;
+pub mod query;
+pub mod report;
 pub mod sequence;
+pub mod serial;
+pub mod source;
 #[cfg(test)]
 pub mod tests;
 pub mod token;
+pub mod visit;
@@ -0,0 +1,54 @@
+use core::ops::Range;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::source::*;
+use crate::token::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrammarError {
+    pub message: String,
+    pub rule: Option<String>,
+    pub span: Range<usize>,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl GrammarError {
+    pub fn new(message: impl Into<String>, text: &str, span: Range<usize>) -> Self {
+        let (line, column) = line_col(text, span.start);
+        Self {
+            message: message.into(),
+            rule: None,
+            span,
+            line,
+            column,
+        }
+    }
+
+    pub fn at_token(message: impl Into<String>, token: &Token<'_>) -> Self {
+        let span = token
+            .content_range()
+            .unwrap_or(token.source.len()..token.source.len());
+        Self::new(message, token.source, span)
+    }
+
+    pub fn in_rule(mut self, rule: &str) -> Self {
+        if self.rule.is_none() {
+            self.rule = Some(rule.to_string());
+        }
+        self
+    }
+}
+
+impl Display for GrammarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)?;
+        if let Some(rule) = &self.rule {
+            write!(f, " (in rule `{rule}`)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for GrammarError {}
@@ -0,0 +1,229 @@
+use core::ops::Range;
+use std::collections::{HashMap, HashSet};
+
+use crate::sequence::*;
+
+// The shape of a rule as written in the meta grammar, before it is turned
+// into `Sequence`s. Code generators work from this rather than from the
+// boxed sequences, which cannot be inspected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleDef {
+    pub name: String,
+    pub seq: SeqDef,
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeqDef {
+    Mult(Vec<(SeqDef, String)>),
+    // A `Mult` that commits after its first `usize` items.
+    Cut(Vec<(SeqDef, String)>, usize),
+    Choose(Vec<(SeqDef, String)>),
+    OneOrMore(Box<SeqDef>, String),
+    NoneOrMore(Box<SeqDef>, String),
+    // Between a minimum and an optional maximum number of matches.
+    Repeat(Box<SeqDef>, String, usize, Option<usize>),
+    // Items and their name, separators and theirs (dropped if empty), the
+    // minimum count and whether a trailing separator is allowed.
+    Sep(Box<SeqDef>, String, Box<SeqDef>, String, usize, bool),
+    Opt(Box<SeqDef>, String),
+    Except(Box<SeqDef>),
+    Recover(Box<SeqDef>, Option<Box<SeqDef>>),
+    // Negated if the flag is set.
+    Lookahead(Box<SeqDef>, bool),
+    Raw(String),
+    Chars(String),
+    Range(u32, u32),
+    Ref(String),
+}
+
+impl SeqDef {
+    pub fn build(&self) -> Box<dyn Sequence> {
+        let build_all = |seqs: &[(SeqDef, String)]| {
+            seqs.iter()
+                .map(|(seq, name)| (seq.build(), name.clone()))
+                .collect()
+        };
+        match self {
+            SeqDef::Mult(seqs) => Box::new(MultSeq::new(build_all(seqs))),
+            SeqDef::Cut(seqs, cut) => Box::new(CutSeq::new(build_all(seqs), *cut)),
+            SeqDef::Choose(seqs) => Box::new(ChooseSeq::new(build_all(seqs))),
+            SeqDef::OneOrMore(seq, name) => Box::new(OneOrMoreSeq::new(seq.build(), name.clone())),
+            SeqDef::NoneOrMore(seq, name) => {
+                Box::new(NoneOrMoreSeq::new(seq.build(), name.clone()))
+            }
+            SeqDef::Repeat(seq, name, min, max) => {
+                Box::new(RepeatSeq::new(seq.build(), name.clone(), *min, *max))
+            }
+            SeqDef::Sep(seq, name, sep, sep_name, min, trailing) => {
+                let sep_name = Some(sep_name.clone()).filter(|name| !name.is_empty());
+                Box::new(SepSeq::new(
+                    seq.build(),
+                    name.clone(),
+                    sep.build(),
+                    sep_name,
+                    *min,
+                    *trailing,
+                ))
+            }
+            SeqDef::Opt(seq, name) => Box::new(OptSeq::new(seq.build(), name.clone())),
+            SeqDef::Except(seq) => Box::new(ExceptSeq::new(seq.build())),
+            SeqDef::Lookahead(seq, negate) => Box::new(LookaheadSeq::new(seq.build(), *negate)),
+            SeqDef::Recover(seq, sync) => Box::new(RecoverSeq::new(
+                seq.build(),
+                sync.as_ref().map(|sync| sync.build()),
+            )),
+            SeqDef::Raw(text) => Box::new(RawSeq::new(text.clone())),
+            SeqDef::Chars(chars) => Box::new(ChooseSeq::from_chars(chars)),
+            SeqDef::Range(start, end) => Box::new(RangeSeq::new(*start, *end)),
+            SeqDef::Ref(name) => Box::new(RefSeq::new(name.clone())),
+        }
+    }
+
+    // Rules referenced anywhere inside this sequence, in order of appearance.
+    pub fn refs(&self) -> Vec<&str> {
+        let mut refs = vec![];
+        self.collect_refs(&mut refs);
+        refs
+    }
+
+    // Whether this can match without consuming input, given the same for
+    // each rule. Unsure cases answer yes, which only makes more rules count
+    // as left-recursive.
+    pub fn nullable(&self, rule_nullable: &dyn Fn(&str) -> bool) -> bool {
+        match self {
+            SeqDef::Mult(seqs) | SeqDef::Cut(seqs, _) => {
+                seqs.iter().all(|(seq, _)| seq.nullable(rule_nullable))
+            }
+            SeqDef::Choose(seqs) => seqs.iter().any(|(seq, _)| seq.nullable(rule_nullable)),
+            SeqDef::OneOrMore(seq, _) => seq.nullable(rule_nullable),
+            SeqDef::Repeat(seq, _, min, _) => *min == 0 || seq.nullable(rule_nullable),
+            SeqDef::Sep(seq, _, _, _, min, _) => *min == 0 || seq.nullable(rule_nullable),
+            SeqDef::NoneOrMore(..)
+            | SeqDef::Opt(..)
+            | SeqDef::Lookahead(..)
+            | SeqDef::Recover(..) => true,
+            SeqDef::Ref(name) => rule_nullable(name),
+            SeqDef::Except(_) | SeqDef::Raw(_) | SeqDef::Chars(_) | SeqDef::Range(..) => false,
+        }
+    }
+
+    // Rules this may call at the position it starts at.
+    fn left_refs<'a>(&'a self, rule_nullable: &dyn Fn(&str) -> bool, refs: &mut Vec<&'a str>) {
+        match self {
+            SeqDef::Mult(seqs) | SeqDef::Cut(seqs, _) => {
+                for (seq, _) in seqs {
+                    seq.left_refs(rule_nullable, refs);
+                    if !seq.nullable(rule_nullable) {
+                        break;
+                    }
+                }
+            }
+            SeqDef::Choose(seqs) => {
+                for (seq, _) in seqs {
+                    seq.left_refs(rule_nullable, refs);
+                }
+            }
+            SeqDef::Sep(seq, _, sep, ..) => {
+                seq.left_refs(rule_nullable, refs);
+                if seq.nullable(rule_nullable) {
+                    sep.left_refs(rule_nullable, refs);
+                }
+            }
+            SeqDef::Recover(seq, sync) => {
+                seq.left_refs(rule_nullable, refs);
+                if let Some(sync) = sync {
+                    sync.left_refs(rule_nullable, refs);
+                }
+            }
+            SeqDef::OneOrMore(seq, _)
+            | SeqDef::NoneOrMore(seq, _)
+            | SeqDef::Repeat(seq, ..)
+            | SeqDef::Opt(seq, _)
+            | SeqDef::Except(seq)
+            | SeqDef::Lookahead(seq, _) => seq.left_refs(rule_nullable, refs),
+            SeqDef::Ref(name) => refs.push(name),
+            SeqDef::Raw(_) | SeqDef::Chars(_) | SeqDef::Range(..) => {}
+        }
+    }
+
+    fn collect_refs<'a>(&'a self, refs: &mut Vec<&'a str>) {
+        match self {
+            SeqDef::Mult(seqs) | SeqDef::Cut(seqs, _) | SeqDef::Choose(seqs) => {
+                for (seq, _) in seqs {
+                    seq.collect_refs(refs);
+                }
+            }
+            SeqDef::OneOrMore(seq, _)
+            | SeqDef::NoneOrMore(seq, _)
+            | SeqDef::Repeat(seq, ..)
+            | SeqDef::Opt(seq, _)
+            | SeqDef::Except(seq)
+            | SeqDef::Lookahead(seq, _) => seq.collect_refs(refs),
+            SeqDef::Sep(seq, _, sep, ..) => {
+                seq.collect_refs(refs);
+                sep.collect_refs(refs);
+            }
+            SeqDef::Recover(seq, sync) => {
+                seq.collect_refs(refs);
+                if let Some(sync) = sync {
+                    sync.collect_refs(refs);
+                }
+            }
+            SeqDef::Ref(name) => refs.push(name),
+            SeqDef::Raw(_) | SeqDef::Chars(_) | SeqDef::Range(..) => {}
+        }
+    }
+}
+
+// The rules that can call themselves, directly or through other rules, at
+// the position they started at. Only these need seeds to grow; see
+// `ParseContext::call_rule`. A later rule with the same name replaces an
+// earlier one, as in `eval_rule_set`.
+pub fn left_recursive_rules(rules: &[RuleDef]) -> HashSet<&str> {
+    let defs: HashMap<&str, &SeqDef> = rules
+        .iter()
+        .map(|rule| (rule.name.as_str(), &rule.seq))
+        .collect();
+    let builtin_nullable = |name: &str| name == "ws" || name == "_";
+    let mut nullable: HashSet<&str> = HashSet::new();
+    loop {
+        let found: Vec<&str> = defs
+            .iter()
+            .filter(|(name, def)| {
+                !nullable.contains(*name)
+                    && def.nullable(&|rule| nullable.contains(rule) || builtin_nullable(rule))
+            })
+            .map(|(name, _)| *name)
+            .collect();
+        if found.is_empty() {
+            break;
+        }
+        nullable.extend(found);
+    }
+    let rule_nullable = |rule: &str| nullable.contains(rule) || builtin_nullable(rule);
+    let left: HashMap<&str, Vec<&str>> = defs
+        .iter()
+        .map(|(name, def)| {
+            let mut refs = vec![];
+            def.left_refs(&rule_nullable, &mut refs);
+            (*name, refs)
+        })
+        .collect();
+    defs.keys()
+        .copied()
+        .filter(|&start| {
+            let mut seen = HashSet::new();
+            let mut stack = left[start].clone();
+            while let Some(rule) = stack.pop() {
+                if rule == start {
+                    return true;
+                }
+                if seen.insert(rule) {
+                    stack.extend(left.get(rule).into_iter().flatten());
+                }
+            }
+            false
+        })
+        .collect()
+}
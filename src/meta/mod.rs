@@ -2,6 +2,12 @@ use crate::corpus::*;
 use crate::sequence::*;
 use crate::token::*;
 use test_case::test_case;
+use unicode_segmentation::UnicodeSegmentation;
+
+mod error;
+mod grammar;
+pub use error::*;
+pub use grammar::*;
 
 pub fn meta_seqs() -> RefMap {
     let mut map = RefMap::new();
@@ -54,7 +60,7 @@ pub fn meta_seqs() -> RefMap {
                 Box::new(NoneOrMoreSeq::new(
                     Box::new(MultSeq::new(vec![
                         (Box::new(RefSeq::new("ws*".to_string())), "".to_string()),
-                        (Box::new(ChooseSeq::from_chars("&+")), "oper".to_string()),
+                        (Box::new(ChooseSeq::from_chars("&+~")), "oper".to_string()),
                         (Box::new(RefSeq::new("ws*".to_string())), "".to_string()),
                         (
                             Box::new(RefSeq::new("noMultSeq".to_string())),
@@ -106,6 +112,22 @@ pub fn meta_seqs() -> RefMap {
     map.insert(
         "noChooseSeq".to_string(),
         Box::new(ChooseSeq::new(vec![
+            (
+                Box::new(RefSeq::new("lookaheadSeq".to_string())),
+                "lookahead".to_string(),
+            ),
+            (
+                Box::new(RefSeq::new("recoverSeq".to_string())),
+                "recover".to_string(),
+            ),
+            (
+                Box::new(RefSeq::new("sepSeq".to_string())),
+                "sep".to_string(),
+            ),
+            (
+                Box::new(RefSeq::new("repeatSeq".to_string())),
+                "repeat".to_string(),
+            ),
             (
                 Box::new(RefSeq::new("oneSuffixSeq".to_string())),
                 "oneSuffix".to_string(),
@@ -130,6 +152,117 @@ pub fn meta_seqs() -> RefMap {
             (Box::new(ChooseSeq::from_chars("+*?!")), "plus".to_string()),
         ])),
     );
+    map.insert(
+        "number".to_string(),
+        Box::new(OneOrMoreSeq::new(
+            Box::new(RangeSeq::new('0' as u32, '9' as u32)),
+            "".to_string(),
+        )),
+    );
+    map.insert(
+        "repeatSeq".to_string(),
+        Box::new(MultSeq::new(vec![
+            (
+                Box::new(RefSeq::new("oneSeq".to_string())),
+                "seq".to_string(),
+            ),
+            (
+                Box::new(RefSeq::new("optName".to_string())),
+                "name".to_string(),
+            ),
+            (Box::new(RawSeq::new("{".to_string())), "".to_string()),
+            (
+                Box::new(RefSeq::new("number".to_string())),
+                "min".to_string(),
+            ),
+            (
+                Box::new(OptSeq::new(
+                    Box::new(MultSeq::new(vec![
+                        (Box::new(RawSeq::new(",".to_string())), "".to_string()),
+                        (
+                            Box::new(OptSeq::new(
+                                Box::new(RefSeq::new("number".to_string())),
+                                "max".to_string(),
+                            )),
+                            "max".to_string(),
+                        ),
+                    ])),
+                    "upper".to_string(),
+                )),
+                "upper".to_string(),
+            ),
+            (Box::new(RawSeq::new("}".to_string())), "".to_string()),
+        ])),
+    );
+    map.insert(
+        "sepSeq".to_string(),
+        Box::new(MultSeq::new(vec![
+            (
+                Box::new(RefSeq::new("oneSeq".to_string())),
+                "seq".to_string(),
+            ),
+            (
+                Box::new(RefSeq::new("optName".to_string())),
+                "name".to_string(),
+            ),
+            (Box::new(RefSeq::new("ws*".to_string())), "".to_string()),
+            (Box::new(RawSeq::new("%".to_string())), "".to_string()),
+            (
+                Box::new(OptSeq::new(
+                    Box::new(RawSeq::new("%".to_string())),
+                    "trailing".to_string(),
+                )),
+                "trailing".to_string(),
+            ),
+            (
+                Box::new(OptSeq::new(
+                    Box::new(RefSeq::new("number".to_string())),
+                    "min".to_string(),
+                )),
+                "min".to_string(),
+            ),
+            (Box::new(RefSeq::new("ws*".to_string())), "".to_string()),
+            (
+                Box::new(RefSeq::new("oneSeq".to_string())),
+                "sep".to_string(),
+            ),
+            (
+                Box::new(RefSeq::new("optName".to_string())),
+                "sepName".to_string(),
+            ),
+        ])),
+    );
+    map.insert(
+        "lookaheadSeq".to_string(),
+        Box::new(MultSeq::new(vec![
+            (Box::new(ChooseSeq::from_chars("&!")), "oper".to_string()),
+            (
+                Box::new(RefSeq::new("oneSeq".to_string())),
+                "seq".to_string(),
+            ),
+        ])),
+    );
+    map.insert(
+        "recoverSeq".to_string(),
+        Box::new(MultSeq::new(vec![
+            (
+                Box::new(RefSeq::new("oneSeq".to_string())),
+                "seq".to_string(),
+            ),
+            (
+                Box::new(RefSeq::new("optName".to_string())),
+                "name".to_string(),
+            ),
+            (Box::new(RawSeq::new("@".to_string())), "".to_string()),
+            (
+                Box::new(OptSeq::new(
+                    Box::new(RefSeq::new("oneSeq".to_string())),
+                    "sync".to_string(),
+                )),
+                "sync".to_string(),
+            ),
+        ])),
+    );
     map.insert(
         "oneSeq".to_string(),
         Box::new(ChooseSeq::new(vec![
@@ -173,6 +306,22 @@ pub fn meta_seqs() -> RefMap {
     map.insert(
         "rangeSeq".to_string(),
         Box::new(MultSeq::new(vec![
+            // Checks for the `..` silently first, so that a token that does
+            // not start a range fails where it is rather than one token on.
+            (
+                Box::new(LookaheadSeq::new(
+                    Box::new(LookaheadSeq::new(
+                        Box::new(MultSeq::new(vec![
+                            (Box::new(AnySeq::new()), "".to_string()),
+                            (Box::new(RawSeq::new(".".to_string())), "".to_string()),
+                            (Box::new(RawSeq::new(".".to_string())), "".to_string()),
+                        ])),
+                        true,
+                    )),
+                    true,
+                )),
+                "".to_string(),
+            ),
             (Box::new(AnySeq::new()), "start".to_string()),
             (Box::new(RawSeq::new(".".to_string())), "".to_string()),
             (Box::new(RawSeq::new(".".to_string())), "".to_string()),
@@ -252,180 +401,336 @@ pub fn meta_seqs() -> RefMap {
 }
 
 pub fn eval_rule_set(text: &str) -> RefMap {
+    try_eval_rule_set(text).unwrap_or_else(|e| panic!("{e}"))
+}
+
+pub fn try_eval_rule_set(text: &str) -> Result<RefMap, GrammarError> {
     let mut map = RefMap::new();
     map.insert("ws".to_string(), Box::new(WhitespaceSeq::new()));
     map.insert("_".to_string(), Box::new(NilSeq::new()));
+    map.insert("any".to_string(), Box::new(AnySeq::new()));
+    for rule in read_rule_set(text)? {
+        map.insert(rule.name, rule.seq.build());
+    }
+    Ok(map)
+}
+
+pub fn read_rule_set(text: &str) -> Result<Vec<RuleDef>, GrammarError> {
     let seqs = meta_seqs();
-    let seq = seqs.get("main").unwrap();
-    let matched = seq
-        .match_corpus_first(&Corpus::make(text), &seqs)
-        .unwrap()
-        .new_token;
-    for token in matched.get_children("rule") {
-        let eval = eval_rule(&token);
-        map.insert(eval.0, eval.1);
+    let corpus = Corpus::make(text);
+    let mut ctx = ParseContext::new(&seqs);
+    let matched = seqs["main"]
+        .match_at(&corpus.tokens, 0, &mut ctx)
+        .ok_or_else(|| GrammarError::new("could not parse grammar", text, 0..text.len()))?;
+    let rest = &corpus.tokens[matched.len..];
+    let trailing = seqs["ws*"].match_tokens(rest, &seqs).map_or(0, |m| m.len);
+    if trailing < rest.len() {
+        let rules = matched.new_token.children_named("rule");
+        let last = rules.last().and_then(|rule| rule.child("name"));
+        return Err(unparsed_error(
+            text,
+            &corpus.tokens,
+            matched.len,
+            last.map(|name| name.content()),
+            &ctx,
+            &seqs,
+        ));
     }
-    map
+    matched
+        .new_token
+        .children_named("rule")
+        .map(read_rule)
+        .collect()
+}
+
+// `main` stops before the first rule it cannot read, so the furthest failure
+// of the meta parse is where that rule went wrong. A failure past the name
+// that the unread input (after `read` tokens) starts with is in that rule;
+// any other is in `last`, the last rule read, whose body could not go on.
+fn unparsed_error(
+    text: &str,
+    tokens: &[Token<'_>],
+    read: usize,
+    last: Option<&str>,
+    ctx: &ParseContext<'_>,
+    seqs: &RefMap,
+) -> GrammarError {
+    let error = ctx.error(tokens);
+    let mut expected: Vec<String> = error
+        .expected
+        .iter()
+        .filter_map(describe_expected)
+        .collect();
+    expected.dedup();
+    let message = match expected.as_slice() {
+        [] => "unexpected input".to_string(),
+        [one] => format!("expected {one}"),
+        many => format!("expected one of {}", many.join(", ")),
+    };
+    let found = match tokens.get(error.index) {
+        Some(token) => format!("{message}, found `{}`", token.content()),
+        None => format!("{message}, found end of input"),
+    };
+    let err = GrammarError::new(found, text, error.span);
+    let start = read
+        + seqs["ws*"]
+            .match_tokens(&tokens[read..], seqs)
+            .map_or(0, |m| m.len);
+    let word = seqs["word"].match_tokens(&tokens[start..], seqs);
+    match (word.filter(|_| error.index > start), last) {
+        (Some(word), _) => err.in_rule(word.new_token.content()),
+        (None, Some(last)) => err.in_rule(last),
+        (None, None) => err,
+    }
+}
+
+// Whitespace is optional everywhere in the meta grammar, so it is never
+// what is missing, and a failed negative lookahead only ever stands in front
+// of something else that failed.
+fn describe_expected(expected: &Expected) -> Option<String> {
+    match expected {
+        Expected::Literal(text) if text.trim().is_empty() => None,
+        Expected::Except => None,
+        Expected::Rule(rule) if rule == "ws*" => None,
+        Expected::Rule(rule) if rule == "word" || rule == "wordChar" => Some("a name".to_string()),
+        Expected::Rule(rule) if rule == "number" => Some("a number".to_string()),
+        Expected::Rule(rule) if rule == "optName" => Some("a `.name`".to_string()),
+        Expected::Rule(rule) if rule == "optMultName" => Some("a `:name`".to_string()),
+        Expected::Rule(rule) if rule.ends_with("eq") => Some("a sequence".to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn child<'t, 'a>(token: &'t Token<'a>, key: &str) -> Result<&'t Token<'a>, GrammarError> {
+    token
+        .child(key)
+        .ok_or_else(|| GrammarError::at_token(format!("expected `{key}` here"), token))
+}
+
+fn opt_name(token: &Token<'_>) -> Result<String, GrammarError> {
+    let opt = child(token, "name")?;
+    match opt.child("name") {
+        Some(named) => Ok(child(named, "name")?.content().to_string()),
+        None => Ok("".to_string()),
+    }
+}
+
+pub fn eval_rule(rule: &Token<'_>) -> Result<(String, Box<dyn Sequence>), GrammarError> {
+    let rule = read_rule(rule)?;
+    Ok((rule.name, rule.seq.build()))
+}
+
+pub fn read_rule(rule: &Token<'_>) -> Result<RuleDef, GrammarError> {
+    let name = child(rule, "name")?.content().to_string();
+    let seq = read_seq(child(rule, "seq")?).map_err(|e| e.in_rule(&name))?;
+    Ok(RuleDef {
+        name,
+        seq,
+        span: rule.content_range().unwrap_or_default(),
+    })
+}
+
+pub fn eval_seq(token: &Token<'_>) -> Result<Box<dyn Sequence>, GrammarError> {
+    read_seq(token).map(|seq| seq.build())
+}
+
+pub fn eval_no_mult_seq(token: &Token<'_>) -> Result<Box<dyn Sequence>, GrammarError> {
+    read_no_mult_seq(token).map(|seq| seq.build())
 }
 
-pub fn eval_rule(rule: &Token<'_>) -> (String, Box<dyn Sequence>) {
-    let rule_name = rule.get_first_child("name").unwrap();
-    let seq = eval_seq(&rule.get_first_child("seq").unwrap());
-    (rule_name.content().to_string(), seq)
+pub fn eval_no_choose_seq(token: &Token<'_>) -> Result<Box<dyn Sequence>, GrammarError> {
+    read_no_choose_seq(token).map(|seq| seq.build())
 }
 
-pub fn eval_seq(token: &Token<'_>) -> Box<dyn Sequence> {
-    let seq = eval_no_mult_seq(&token.get_first_child("lhs").unwrap());
-    let name = token
-        .get_first_child("name")
-        .unwrap()
-        .get_first_child("name")
-        .map_or_else(
-            || "".to_string(),
-            |t| t.get_first_child("name").unwrap().content().to_string(),
-        );
-    let mut to_ret = vec![(seq, name)];
-    let rhs_s = token.get_first_child("rhs's").unwrap();
-    let rhs_s = rhs_s.get_children("rhs's");
-    for rhs in rhs_s {
-        if rhs.get_first_child("oper").unwrap().content() == "+" {
-            to_ret.push((Box::new(RefSeq::new("ws".to_string())), "".to_string()))
+pub fn eval_suffix_seq(token: &Token<'_>) -> Result<Box<dyn Sequence>, GrammarError> {
+    read_suffix_seq(token).map(|seq| seq.build())
+}
+
+pub fn eval_one_seq(token: &Token<'_>) -> Result<Box<dyn Sequence>, GrammarError> {
+    read_one_seq(token).map(|seq| seq.build())
+}
+
+pub fn read_seq(token: &Token<'_>) -> Result<SeqDef, GrammarError> {
+    let seq = read_no_mult_seq(child(token, "lhs")?)?;
+    let mut to_ret = vec![(seq, opt_name(token)?)];
+    // `~` joins like `&`, and the first one commits to the sequence.
+    let mut cut = None;
+    let rhs_s = child(token, "rhs's")?;
+    for rhs in rhs_s.children_named("rhs's") {
+        match child(rhs, "oper")?.content() {
+            "+" => to_ret.push((SeqDef::Ref("ws".to_string()), "".to_string())),
+            "~" => {
+                cut.get_or_insert(to_ret.len());
+            }
+            _ => {}
         }
-        let seq = eval_no_mult_seq(&rhs.get_first_child("seq").unwrap());
-        let name = rhs
-            .get_first_child("name")
-            .unwrap()
-            .get_first_child("name")
-            .map_or_else(
-                || "".to_string(),
-                |t| t.get_first_child("name").unwrap().content().to_string(),
-            );
-        to_ret.push((seq, name));
+        let seq = read_no_mult_seq(child(rhs, "seq")?)?;
+        to_ret.push((seq, opt_name(rhs)?));
     }
-    if to_ret.len() == 1 {
-        to_ret.into_iter().next().unwrap().0
-    } else {
-        Box::new(MultSeq::new(to_ret))
+    match (to_ret.len(), cut) {
+        (1, _) => Ok(to_ret.into_iter().next().unwrap().0),
+        (_, Some(cut)) => Ok(SeqDef::Cut(to_ret, cut)),
+        (_, None) => Ok(SeqDef::Mult(to_ret)),
     }
 }
 
-pub fn eval_no_mult_seq(token: &Token<'_>) -> Box<dyn Sequence> {
-    let st = token.get_first_child("lhs").unwrap();
-    let seq = eval_no_choose_seq(&st);
-    let name = token
-        .get_first_child("name")
-        .unwrap()
-        .get_first_child("name")
-        .map_or_else(
-            || "".to_string(),
-            |t| t.get_first_child("name").unwrap().content().to_string(),
-        );
-    let mut to_ret = vec![(seq, name)];
-    let rhs_s = token.get_first_child("rhs's").unwrap();
-    let rhs_s = rhs_s.get_children("rhs's");
-    for rhs in rhs_s {
-        let st = rhs.get_first_child("seq").unwrap();
-        let seq = eval_no_choose_seq(&st);
-        let name = rhs
-            .get_first_child("name")
-            .unwrap()
-            .get_first_child("name")
-            .map_or_else(
-                || "".to_string(),
-                |t| t.get_first_child("name").unwrap().content().to_string(),
-            );
-        to_ret.push((seq, name));
+pub fn read_no_mult_seq(token: &Token<'_>) -> Result<SeqDef, GrammarError> {
+    let seq = read_no_choose_seq(child(token, "lhs")?)?;
+    let mut to_ret = vec![(seq, opt_name(token)?)];
+    let rhs_s = child(token, "rhs's")?;
+    for rhs in rhs_s.children_named("rhs's") {
+        let seq = read_no_choose_seq(child(rhs, "seq")?)?;
+        to_ret.push((seq, opt_name(rhs)?));
     }
     if to_ret.len() == 1 {
-        to_ret.into_iter().next().unwrap().0
+        Ok(to_ret.into_iter().next().unwrap().0)
     } else {
-        Box::new(ChooseSeq::new(to_ret))
+        Ok(SeqDef::Choose(to_ret))
     }
 }
 
-pub fn eval_no_choose_seq(token: &Token<'_>) -> Box<dyn Sequence> {
-    if let Some(one) = token.get_first_child("one") {
-        eval_one_seq(&one)
-    } else if let Some(suffix) = token.get_first_child("oneSuffix") {
-        eval_suffix_seq(&suffix)
+pub fn read_no_choose_seq(token: &Token<'_>) -> Result<SeqDef, GrammarError> {
+    if let Some(one) = token.child("one") {
+        read_one_seq(one)
+    } else if let Some(lookahead) = token.child("lookahead") {
+        read_lookahead_seq(lookahead)
+    } else if let Some(recover) = token.child("recover") {
+        read_recover_seq(recover)
+    } else if let Some(sep) = token.child("sep") {
+        read_sep_seq(sep)
+    } else if let Some(repeat) = token.child("repeat") {
+        read_repeat_seq(repeat)
+    } else if let Some(suffix) = token.child("oneSuffix") {
+        read_suffix_seq(suffix)
     } else {
-        let suffix = token.get_first_child("suffix").unwrap();
-        eval_suffix_seq(&suffix)
+        read_suffix_seq(child(token, "suffix")?)
     }
 }
 
-pub fn eval_suffix_seq(token: &Token<'_>) -> Box<dyn Sequence> {
-    let child = &token.get_first_child("seq").unwrap();
-    let seq = eval_one_seq(child);
-    let name = token
-        .get_first_child("name")
-        .unwrap()
-        .get_first_child("name")
-        .map_or_else(
-            || "".to_string(),
-            |t| t.get_first_child("name").unwrap().content().to_string(),
-        );
-    let plus = token.get_first_child("plus").unwrap().content() == "+";
-    let many = token.get_first_child("plus").unwrap().content() == "*";
-    let opt = token.get_first_child("plus").unwrap().content() == "?";
-    let except = token.get_first_child("plus").unwrap().content() == "!";
-    if plus {
-        Box::new(OneOrMoreSeq::new(seq, name))
-    } else if many {
-        Box::new(NoneOrMoreSeq::new(seq, name))
-    } else if opt {
-        Box::new(OptSeq::new(seq, name))
-    } else if except {
-        Box::new(ExceptSeq::new(seq))
-    } else {
-        seq
+pub fn read_suffix_seq(token: &Token<'_>) -> Result<SeqDef, GrammarError> {
+    let seq = Box::new(read_one_seq(child(token, "seq")?)?);
+    let name = opt_name(token)?;
+    let plus = child(token, "plus")?;
+    match plus.content() {
+        "+" => Ok(SeqDef::OneOrMore(seq, name)),
+        "*" => Ok(SeqDef::NoneOrMore(seq, name)),
+        "?" => Ok(SeqDef::Opt(seq, name)),
+        "!" => Ok(SeqDef::Except(seq)),
+        other => Err(GrammarError::at_token(
+            format!("unknown suffix `{other}`"),
+            plus,
+        )),
     }
 }
 
-pub fn eval_one_seq(token: &Token<'_>) -> Box<dyn Sequence> {
-    if let Some(raw) = token.get_first_child("raw") {
-        Box::new(RawSeq::new(
-            raw.get_first_child("token").unwrap().content().to_string(),
-        ))
-    } else if let Some(ref_name) = token.get_first_child("ref") {
-        Box::new(RefSeq::new(ref_name.content().to_string()))
-    } else if let Some(seq_t) = token.get_first_child("paren") {
-        eval_seq(&seq_t.get_first_child("seq").unwrap())
-    } else if let Some(seq_t) = token.get_first_child("quote") {
-        let in_tox = seq_t.get_first_child("chars").unwrap();
-        Box::new(MultSeq::new(
+// `seq{n}` matches exactly `n` times, `seq{m,n}` between `m` and `n` times
+// and `seq{m,}` at least `m` times.
+pub fn read_repeat_seq(token: &Token<'_>) -> Result<SeqDef, GrammarError> {
+    let seq = Box::new(read_one_seq(child(token, "seq")?)?);
+    let name = opt_name(token)?;
+    let min = read_number(child(token, "min")?)?;
+    let max = match child(token, "upper")?.child("upper") {
+        Some(upper) => match child(upper, "max")?.child("max") {
+            Some(max) => Some(read_number(max)?),
+            None => None,
+        },
+        None => Some(min),
+    };
+    if let Some(max) = max.filter(|&max| max < min) {
+        return Err(GrammarError::at_token(
+            format!("repeat maximum {max} is less than its minimum {min}"),
+            token,
+        ));
+    }
+    Ok(SeqDef::Repeat(seq, name, min, max))
+}
+
+// `item % sep` matches any number of `item`s separated by `sep`. `%%` also
+// takes a trailing separator, and a number after the `%` sets the minimum
+// count. Separators are kept only if named, as in `item % ','.comma`.
+pub fn read_sep_seq(token: &Token<'_>) -> Result<SeqDef, GrammarError> {
+    let seq = Box::new(read_one_seq(child(token, "seq")?)?);
+    let sep = Box::new(read_one_seq(child(token, "sep")?)?);
+    let sep_name = match child(token, "sepName")?.child("name") {
+        Some(named) => child(named, "name")?.content().to_string(),
+        None => "".to_string(),
+    };
+    let min = match child(token, "min")?.child("min") {
+        Some(min) => read_number(min)?,
+        None => 0,
+    };
+    let trailing = child(token, "trailing")?.child("trailing").is_some();
+    Ok(SeqDef::Sep(
+        seq,
+        opt_name(token)?,
+        sep,
+        sep_name,
+        min,
+        trailing,
+    ))
+}
+
+fn read_number(token: &Token<'_>) -> Result<usize, GrammarError> {
+    token
+        .content()
+        .parse()
+        .map_err(|_| GrammarError::at_token("count is too large", token))
+}
+
+// `&seq` matches nothing if `seq` matches, and `!seq` if it does not.
+pub fn read_lookahead_seq(token: &Token<'_>) -> Result<SeqDef, GrammarError> {
+    let seq = Box::new(read_one_seq(child(token, "seq")?)?);
+    Ok(SeqDef::Lookahead(
+        seq,
+        child(token, "oper")?.content() == "!",
+    ))
+}
+
+// `seq@sync` skips through `sync` when `seq` fails during a recovering parse;
+// a bare `seq@` skips to the end of the line.
+pub fn read_recover_seq(token: &Token<'_>) -> Result<SeqDef, GrammarError> {
+    let seq = Box::new(read_one_seq(child(token, "seq")?)?);
+    let sync = match child(token, "sync")?.child("sync") {
+        Some(sync) => Some(Box::new(read_one_seq(sync)?)),
+        None => None,
+    };
+    Ok(SeqDef::Recover(seq, sync))
+}
+
+fn first_char(token: &Token<'_>) -> Result<u32, GrammarError> {
+    token
+        .content()
+        .chars()
+        .next()
+        .map(|c| c as u32)
+        .ok_or_else(|| GrammarError::at_token("expected a character", token))
+}
+
+pub fn read_one_seq(token: &Token<'_>) -> Result<SeqDef, GrammarError> {
+    if let Some(raw) = token.child("raw") {
+        Ok(SeqDef::Raw(child(raw, "token")?.content().to_string()))
+    } else if let Some(ref_name) = token.child("ref") {
+        Ok(SeqDef::Ref(ref_name.content().to_string()))
+    } else if let Some(seq_t) = token.child("paren") {
+        read_seq(child(seq_t, "seq")?)
+    } else if let Some(seq_t) = token.child("quote") {
+        let in_tox = child(seq_t, "chars")?;
+        Ok(SeqDef::Mult(
             in_tox
                 .content()
-                .chars()
-                .map(|c| {
-                    (
-                        Box::new(RawSeq::new(c.to_string())) as Box<dyn Sequence>,
-                        "".to_string(),
-                    )
-                })
+                .graphemes(true)
+                .map(|c| (SeqDef::Raw(c.to_string()), "".to_string()))
                 .collect(),
         ))
-    } else if let Some(seq_t) = token.get_first_child("fromChars") {
-        let in_tox = seq_t.get_first_child("chars").unwrap();
-        Box::new(ChooseSeq::from_chars(in_tox.content()))
-    } else if let Some(seq_t) = token.get_first_child("range") {
-        let start = seq_t
-            .get_first_child("start")
-            .unwrap()
-            .content()
-            .chars()
-            .next()
-            .unwrap() as u32;
-        let end = seq_t
-            .get_first_child("end")
-            .unwrap()
-            .content()
-            .chars()
-            .next()
-            .unwrap() as u32;
-        Box::new(RangeSeq::new(start, end))
+    } else if let Some(seq_t) = token.child("fromChars") {
+        let in_tox = child(seq_t, "chars")?;
+        Ok(SeqDef::Chars(in_tox.content().to_string()))
+    } else if let Some(seq_t) = token.child("range") {
+        let start = first_char(child(seq_t, "start")?)?;
+        let end = first_char(child(seq_t, "end")?)?;
+        Ok(SeqDef::Range(start, end))
     } else {
-        unimplemented!()
+        Err(GrammarError::at_token("unknown sequence", token))
     }
 }
 
@@ -497,6 +802,14 @@ main = 'a'?:opt + 'b':req
 ", "b";
 "optional rule")]
 #[test_case("
+main = 0..9{2,3}:year & 0..9.digit{2,} & a..f{4}
+", "1999abcd";
+"repeat rule")]
+#[test_case("
+main = 0..9.item %%1 ','
+", "1,2,3,";
+"separated rule")]
+#[test_case("
 main = ('j'!)?:jay
 ", "kjjkmj";
 "except rule")]
@@ -507,3 +820,67 @@ pub fn test_eval(rules: &str, text: &str) {
     let matched = seq.match_corpus_first(&Corpus::make(text), &seqs);
     println!("{matched:?}");
 }
+
+#[test_case("main 'a'", 1, 6, Some("main"), "expected `=`, found `'`";
+"missing equals")]
+#[test_case("
+main = 'a'
+b = ", 3, 5, Some("b"), "expected a sequence, found end of input";
+"missing sequence")]
+#[test_case("
+main = 'a'
+  = 'b'
+", 3, 3, Some("main"), "expected one of `%`, `&`, `+`, `|`, `~`, a name, found `=`";
+"missing rule name")]
+#[test_case("a = 'x' &", 1, 10, Some("a"), "expected a sequence, found end of input";
+"dangling operator")]
+#[test_case("a = 'x' | | 'y'", 1, 11, Some("a"), "expected a sequence, found `|`";
+"empty alternative")]
+#[test_case("
+a = 'x'
+b = 'y' &", 3, 10, Some("b"), "expected a sequence, found end of input";
+"dangling operator in later rule")]
+#[test_case("main = ('a'", 1, 12, Some("main"),
+"expected one of `!`, `%`, `&`, `)`, `*`, `+`, `?`, `@`, `{`, `|`, `~`, a `:name`, a `.name`, found end of input";
+"unclosed paren")]
+#[test_case("main = ('a' & 'b' 'c')", 1, 19, Some("main"), "expected one of `%`, `&`, `+`, `|`, `~`, found `'`";
+"error inside body")]
+#[test_case("main = 'a'{3,2}", 1, 8, Some("main"), "repeat maximum 2 is less than its minimum 3";
+"backwards repeat")]
+pub fn test_eval_error(rules: &str, line: usize, column: usize, rule: Option<&str>, message: &str) {
+    let err = try_eval_rule_set(rules).err().unwrap();
+    assert_eq!((err.line, err.column), (line, column));
+    assert_eq!(err.rule.as_deref(), rule);
+    assert_eq!(err.message, message);
+}
+
+#[test_case("main = {日本語}", "日本語", "日本語", false; "cjk quote")]
+#[test_case("main = 一..龥+", "漢字かな", "漢字", false; "cjk range")]
+#[test_case("main = α..ω+", "λόγος", "λ", false; "greek range")]
+#[test_case("main = [👍🎉]+ & {!}", "🎉👍!", "🎉👍!", false; "emoji from chars")]
+#[test_case("main = {e\u{301}}+", "e\u{301}e\u{301}", "e\u{301}e\u{301}", false; "combining mark by char")]
+#[test_case("main = {e\u{301}}+", "e\u{301}e\u{301}", "e\u{301}e\u{301}", true; "combining mark by grapheme")]
+#[test_case("main = [ae\u{301}]+", "ae\u{301}ae", "ae\u{301}a", true; "combining mark from chars")]
+pub fn test_eval_unicode(rules: &str, text: &str, expected: &str, graphemes: bool) {
+    let seqs = eval_rule_set(rules);
+    let corpus = if graphemes {
+        Corpus::make_graphemes(text)
+    } else {
+        Corpus::make(text)
+    };
+    let matched = seqs["main"].match_corpus_first(&corpus, &seqs).unwrap();
+    assert_eq!(matched.new_token.content(), expected);
+}
+
+#[test]
+pub fn left_recursive_test() {
+    let rules = read_rule_set(include_str!("../tests/left.tuckey")).unwrap();
+    let mut recursive: Vec<&str> = left_recursive_rules(&rules).into_iter().collect();
+    recursive.sort();
+    assert_eq!(recursive, vec!["a", "b", "expr", "multExpr"]);
+
+    // Calls after something that may match nothing are still at the start.
+    let rules = read_rule_set("x = _ & 'a'? & x & 'b' | 'c'\ny = 'a' & y | 'b'").unwrap();
+    let recursive: Vec<&str> = left_recursive_rules(&rules).into_iter().collect();
+    assert_eq!(recursive, vec!["x"]);
+}
@@ -0,0 +1,213 @@
+use core::ops::Range;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::serial::{Reader, TreeParseError};
+use crate::token::*;
+
+#[cfg(test)]
+mod tests;
+
+// A path through a token tree, in the spirit of XPath:
+// - `a/b` is every child named `b` of every child named `a`;
+// - `a//b` is every `b` anywhere under an `a`, and a leading `//` searches
+//   the whole tree;
+// - `*` matches a child of any name, and names that are not plain words are
+//   written as strings, as in `"rhs's"` or `""` for unnamed children;
+// - `[n]` keeps the n-th match (counting from 1) under each parent,
+//   `[text="+"]` keeps the matches whose content is exactly `+`, and
+//   `[rule="posInt"]` those produced by the rule `posInt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selector {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    deep: bool,
+    name: Option<String>,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Predicate {
+    Index(usize),
+    Text(String),
+    Rule(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub message: String,
+    pub offset: usize,
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at byte {} of the selector",
+            self.message, self.offset
+        )
+    }
+}
+
+impl Error for QueryError {}
+
+impl From<TreeParseError> for QueryError {
+    fn from(err: TreeParseError) -> Self {
+        Self {
+            message: err.message,
+            offset: err.offset,
+        }
+    }
+}
+
+// A token picked out by a selector, with the name it has under its parent.
+#[derive(Debug, Clone)]
+pub struct Selected<'t, 'a> {
+    pub name: &'t str,
+    pub token: &'t Token<'a>,
+    pub span: Option<Range<usize>>,
+}
+
+impl Selector {
+    pub fn parse(text: &str) -> Result<Self, QueryError> {
+        let mut reader = Reader {
+            text,
+            pos: 0,
+            base: 0,
+        };
+        let mut steps = vec![];
+        let mut deep = reader.eat("//");
+        if !deep {
+            reader.eat("/");
+        }
+        loop {
+            steps.push(read_step(&mut reader, deep)?);
+            if reader.pos == text.len() {
+                return Ok(Self { steps });
+            }
+            deep = reader.eat("//");
+            if !deep && !reader.eat("/") {
+                return Err(reader
+                    .error("expected `/` or the end of the selector")
+                    .into());
+            }
+        }
+    }
+
+    pub fn select<'t, 'a>(&self, token: &'t Token<'a>) -> Vec<Selected<'t, 'a>> {
+        let mut current = vec![Selected {
+            name: "",
+            token,
+            span: token.content_range(),
+        }];
+        for step in &self.steps {
+            let parents: Vec<&Token<'a>> = match step.deep {
+                true => dedup(current.iter().flat_map(|s| s.token.descendants())),
+                false => current.iter().map(|s| s.token).collect(),
+            };
+            current = parents
+                .into_iter()
+                .flat_map(|parent| step.apply(parent))
+                .collect();
+        }
+        current
+    }
+}
+
+impl FromStr for Selector {
+    type Err = QueryError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+impl Step {
+    fn apply<'t, 'a>(&self, parent: &'t Token<'a>) -> Vec<Selected<'t, 'a>> {
+        let mut matches: Vec<(&str, &Token<'a>)> = parent
+            .entries()
+            .iter()
+            .filter(|(name, _)| self.name.as_ref().is_none_or(|n| n == name))
+            .map(|(name, child)| (name.as_str(), child))
+            .collect();
+        for predicate in &self.predicates {
+            matches = match predicate {
+                Predicate::Index(n) => matches.get(n - 1).into_iter().copied().collect(),
+                Predicate::Text(text) => matches
+                    .into_iter()
+                    .filter(|(_, token)| token.content() == text)
+                    .collect(),
+                Predicate::Rule(rule) => matches
+                    .into_iter()
+                    .filter(|(_, token)| token.rule == Some(rule.as_str()))
+                    .collect(),
+            };
+        }
+        matches
+            .into_iter()
+            .map(|(name, token)| Selected {
+                name,
+                token,
+                span: token.content_range(),
+            })
+            .collect()
+    }
+}
+
+fn read_step(reader: &mut Reader<'_>, deep: bool) -> Result<Step, QueryError> {
+    let name = if reader.eat("*") {
+        None
+    } else if reader.rest().starts_with('"') {
+        Some(reader.string()?)
+    } else {
+        match reader.word() {
+            "" => return Err(reader.error("expected a name, `*` or a string").into()),
+            word => Some(word.to_string()),
+        }
+    };
+    let mut predicates = vec![];
+    while reader.eat("[") {
+        let predicate = if reader.eat("text=") {
+            Predicate::Text(reader.string()?)
+        } else if reader.eat("rule=") {
+            Predicate::Rule(reader.string()?)
+        } else {
+            match reader.number()? {
+                0 => return Err(reader.error("positions count from 1").into()),
+                n => Predicate::Index(n),
+            }
+        };
+        if !reader.eat("]") {
+            return Err(reader.error("expected `]`").into());
+        }
+        predicates.push(predicate);
+    }
+    Ok(Step {
+        deep,
+        name,
+        predicates,
+    })
+}
+
+// Nested `//` steps reach the same token along several paths.
+fn dedup<'t, 'a>(tokens: impl Iterator<Item = &'t Token<'a>>) -> Vec<&'t Token<'a>> {
+    let mut seen = HashSet::new();
+    tokens
+        .filter(|token| seen.insert(*token as *const Token<'a>))
+        .collect()
+}
+
+impl<'a> Token<'a> {
+    pub fn select<'t>(&'t self, selector: &str) -> Result<Vec<Selected<'t, 'a>>, QueryError> {
+        Ok(Selector::parse(selector)?.select(self))
+    }
+
+    pub fn select_first<'t>(&'t self, selector: &str) -> Result<Option<&'t Token<'a>>, QueryError> {
+        Ok(self.select(selector)?.into_iter().next().map(|s| s.token))
+    }
+}
@@ -0,0 +1,88 @@
+use super::*;
+use crate::corpus::*;
+use crate::meta::*;
+use crate::sequence::*;
+
+fn contents(selected: Vec<Selected<'_, '_>>) -> Vec<String> {
+    selected
+        .into_iter()
+        .map(|s| s.token.content().to_string())
+        .collect()
+}
+
+#[test]
+pub fn select_test() {
+    let refs = eval_rule_set(include_str!("../tests/calc.tuckey"));
+    let corpus = Corpus::make("1 + 2 * 3 - 4");
+    let expr = RefSeq::new("expr".to_string());
+    let token = expr.parse_corpus(&corpus, &refs).unwrap();
+
+    let opers = token.select("rhs_s/rhs_s/oper").unwrap();
+    assert_eq!(contents(opers.clone()), ["+", "-"]);
+    assert_eq!(opers[1].name, "oper");
+    assert_eq!(opers[1].span, Some(10..11));
+    assert_eq!(
+        contents(token.select("rhs_s/rhs_s[2]/oper").unwrap()),
+        ["-"]
+    );
+    assert_eq!(
+        contents(token.select("rhs_s/rhs_s/oper[1]").unwrap()),
+        ["+", "-"]
+    );
+    assert_eq!(contents(token.select("//oper").unwrap()), ["+", "*", "-"]);
+    assert_eq!(contents(token.select("//oper[text=\"-\"]").unwrap()), ["-"]);
+    assert_eq!(contents(token.select("lhs/*").unwrap()), ["1", " ", ""]);
+    assert_eq!(
+        contents(token.select("//*[rule=\"multExpr\"]").unwrap()),
+        ["1 ", "2 * 3", "4"]
+    );
+    // Leaves are never tagged with the rule that made them.
+    assert!(token.select("//*[rule=\"nonzero\"]").unwrap().is_empty());
+    assert_eq!(
+        contents(token.select("//rhs_s//int").unwrap()),
+        ["2", "3", "4"]
+    );
+    assert_eq!(contents(token.select("/lhs/lhs").unwrap()), ["1"]);
+    assert!(token.select("missing").unwrap().is_empty());
+
+    let first = token.select_first("//int").unwrap().unwrap();
+    assert_eq!(first.content(), "1");
+}
+
+#[test]
+pub fn quoted_name_test() {
+    let refs = eval_rule_set("main = 'a' & 'b':b");
+    let corpus = Corpus::make("ab");
+    let token = refs["main"].match_tokens(&corpus.tokens, &refs).unwrap();
+    let token = token.new_token;
+    assert_eq!(contents(token.select("\"\"").unwrap()), ["a"]);
+    assert_eq!(contents(token.select("\"b\"").unwrap()), ["b"]);
+    assert_eq!(contents(token.select("*").unwrap()), ["a", "b"]);
+}
+
+#[test]
+pub fn error_test() {
+    let error = |selector: &str| Selector::parse(selector).unwrap_err().to_string();
+    assert_eq!(
+        error(""),
+        "expected a name, `*` or a string at byte 0 of the selector"
+    );
+    assert_eq!(
+        error("a/"),
+        "expected a name, `*` or a string at byte 2 of the selector"
+    );
+    assert_eq!(
+        error("a[0]"),
+        "positions count from 1 at byte 3 of the selector"
+    );
+    assert_eq!(error("a[1"), "expected `]` at byte 3 of the selector");
+    assert_eq!(
+        error("a b"),
+        "expected `/` or the end of the selector at byte 1 of the selector"
+    );
+    assert_eq!(
+        error("a[text=x]"),
+        "expected a string at byte 7 of the selector"
+    );
+    assert_eq!("a//b[2]".parse::<Selector>(), Selector::parse("/a//b[2]"));
+}
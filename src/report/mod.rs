@@ -0,0 +1,210 @@
+use core::ops::Range;
+
+use crate::meta::GrammarError;
+use crate::sequence::ParseError;
+use crate::source::*;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderStyle {
+    Plain,
+    Ansi,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Range<usize>,
+    pub label: Option<String>,
+    pub secondary: Option<(Range<usize>, String)>,
+    pub hint: Option<String>,
+    pub file: Option<String>,
+}
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, span: Range<usize>) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            label: None,
+            secondary: None,
+            hint: None,
+            file: None,
+        }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Range<usize>, label: impl Into<String>) -> Self {
+        self.secondary = Some((span, label.into()));
+        self
+    }
+
+    pub fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+
+    pub fn with_file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    pub fn render(&self, source: &str, style: RenderStyle) -> String {
+        match style {
+            RenderStyle::Plain => self.render_text(source, false),
+            RenderStyle::Ansi => self.render_text(source, true),
+            RenderStyle::Json => self.render_json(source),
+        }
+    }
+
+    fn render_text(&self, source: &str, color: bool) -> String {
+        let paint = |code: &'static str| if color { code } else { "" };
+        let reset = paint(RESET);
+        let map = SourceMap::new(source);
+        let Position { line, column } = map.position(self.span.start, ColumnUnit::Char);
+        let mut lines = vec![(self.span.clone(), '^', paint(RED), self.label.as_deref())];
+        if let Some((span, label)) = &self.secondary {
+            lines.push((span.clone(), '-', paint(BLUE), Some(label.as_str())));
+        }
+        let width = lines
+            .iter()
+            .map(|(span, ..)| {
+                map.position(span.start, ColumnUnit::Char)
+                    .line
+                    .to_string()
+                    .len()
+            })
+            .max()
+            .unwrap_or(1);
+        let gutter = " ".repeat(width);
+        let blue = paint(BLUE);
+
+        let mut out = format!(
+            "{}error{reset}{}: {}{reset}\n",
+            paint(RED),
+            paint(BOLD),
+            self.message
+        );
+        out += &format!(
+            "{gutter}{blue}-->{reset} {}:{line}:{column}\n",
+            self.file.as_deref().unwrap_or("<input>")
+        );
+        out += &format!("{gutter} {blue}|{reset}\n");
+        for (span, mark, mark_color, label) in lines {
+            let Position { line, column } = map.position(span.start, ColumnUnit::Char);
+            let text = map.line_text(line).unwrap_or("");
+            let rest: String = text.chars().skip(column - 1).collect();
+            let len = source[span.start.min(source.len())..span.end.min(source.len())]
+                .chars()
+                .count()
+                .clamp(1, rest.chars().count().max(1));
+            out += &format!("{blue}{line:>width$} |{reset} {text}\n");
+            out += &format!(
+                "{gutter} {blue}|{reset} {}{mark_color}{}",
+                " ".repeat(column - 1),
+                mark.to_string().repeat(len)
+            );
+            if let Some(label) = label {
+                out += &format!(" {label}");
+            }
+            out += &format!("{reset}\n");
+        }
+        if let Some(hint) = &self.hint {
+            out += &format!("{gutter} {blue}|{reset}\n");
+            out += &format!(
+                "{gutter} {blue}={reset} {}hint{reset}: {hint}\n",
+                paint(CYAN)
+            );
+        }
+        out
+    }
+
+    fn render_json(&self, source: &str) -> String {
+        let (line, column) = line_col(source, self.span.start);
+        let opt = |s: &Option<String>| s.as_deref().map_or("null".to_string(), json_string);
+        let secondary = self
+            .secondary
+            .as_ref()
+            .map_or("null".to_string(), |(span, label)| {
+                let (line, column) = line_col(source, span.start);
+                format!(
+                    "{{\"label\":{},\"line\":{line},\"column\":{column},\"start\":{},\"end\":{}}}",
+                    json_string(label),
+                    span.start,
+                    span.end
+                )
+            });
+        format!(
+            "{{\"message\":{},\"file\":{},\"line\":{line},\"column\":{column},\"start\":{},\"end\":{},\"label\":{},\"secondary\":{secondary},\"hint\":{}}}",
+            json_string(&self.message),
+            opt(&self.file),
+            self.span.start,
+            self.span.end,
+            opt(&self.label),
+            opt(&self.hint)
+        )
+    }
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out + "\""
+}
+
+impl From<&GrammarError> for Diagnostic {
+    fn from(err: &GrammarError) -> Self {
+        let diagnostic = Diagnostic::new(err.message.clone(), err.span.clone());
+        match &err.rule {
+            Some(rule) => diagnostic.with_label(format!("in rule `{rule}`")),
+            None => diagnostic,
+        }
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(err: &ParseError) -> Self {
+        let diagnostic = Diagnostic::new(err.message(), err.span.clone());
+        if err.rules.is_empty() {
+            diagnostic
+        } else {
+            diagnostic.with_label(format!("while parsing {}", err.rules.join(" > ")))
+        }
+    }
+}
+
+impl SourceDb {
+    // Renders a parse error against the file it points into, under that
+    // file's name.
+    pub fn render_error(&self, err: &ParseError, style: RenderStyle) -> String {
+        let diagnostic = Diagnostic::from(err);
+        let diagnostic = match self.name(err.file) {
+            Some(name) => diagnostic.with_file(name),
+            None => diagnostic,
+        };
+        diagnostic.render(self.text(err.file).unwrap_or(""), style)
+    }
+}
@@ -0,0 +1,95 @@
+use super::*;
+use crate::corpus::*;
+use crate::meta::*;
+use crate::sequence::*;
+
+#[test]
+pub fn grammar_error_test() {
+    let rules = "digit = 0..9\nmain 'a'";
+    let err = try_eval_rule_set(rules).err().unwrap();
+    let diagnostic = Diagnostic::from(&err).with_file("calc.tuckey");
+    assert_eq!(
+        diagnostic.render(rules, RenderStyle::Plain),
+        "\
+error: expected `=`, found `'`
+ --> calc.tuckey:2:6
+  |
+2 | main 'a'
+  |      ^ in rule `main`
+"
+    );
+}
+
+#[test]
+pub fn parse_error_test() {
+    let refs = eval_rule_set("main = {let} + word:name + '='\nword = [abc]+");
+    let text = "let abc\n  ; = 4";
+    let err = RefSeq::new("main".to_string())
+        .parse_corpus(&Corpus::make(text), &refs)
+        .unwrap_err();
+    let diagnostic = Diagnostic::from(&err)
+        .with_secondary(0..3, "binding starts here")
+        .with_hint("add `=` after the name");
+    assert_eq!(
+        diagnostic.render(text, RenderStyle::Plain),
+        "\
+error: expected `=`
+ --> <input>:2:3
+  |
+2 |   ; = 4
+  |   ^ while parsing main
+1 | let abc
+  | --- binding starts here
+  |
+  = hint: add `=` after the name
+"
+    );
+    assert!(diagnostic
+        .render(text, RenderStyle::Ansi)
+        .contains("\x1b[1;31merror\x1b[0m"));
+    assert_eq!(
+        diagnostic.render(text, RenderStyle::Json),
+        "{\"message\":\"expected `=`\",\"file\":null,\"line\":2,\"column\":3,\"start\":10,\"end\":11,\
+\"label\":\"while parsing main\",\"secondary\":{\"label\":\"binding starts here\",\"line\":1,\"column\":1,\
+\"start\":0,\"end\":3},\"hint\":\"add `=` after the name\"}"
+    );
+}
+
+#[test]
+pub fn multi_file_error_test() {
+    let mut db = SourceDb::new();
+    let main = db.add("main.txt", "ab[");
+    let include = db.add("include.txt", "c1");
+    let main_corpus = db.corpus(main).unwrap();
+    let mut tokens = main_corpus.tokens[..2].to_vec();
+    tokens.extend(db.corpus(include).unwrap().tokens);
+    tokens.push(main_corpus.tokens[2].clone());
+    let refs = eval_rule_set("main = a..z+ & '['");
+    let err = RefSeq::new("main".to_string())
+        .parse_corpus(&Corpus::new(tokens), &refs)
+        .unwrap_err();
+    assert_eq!(err.file, include);
+    assert_eq!((err.span.clone(), err.line, err.column), (1..2, 1, 2));
+    assert_eq!(
+        db.render_error(&err, RenderStyle::Plain),
+        "\
+error: expected one of `[`, `a`..`z`
+ --> include.txt:1:2
+  |
+1 | c1
+  |  ^ while parsing main
+"
+    );
+
+    // Past the last token, the error points into the last token's file.
+    let tokens = db.corpus(include).unwrap().tokens[..1].to_vec();
+    let err = RefSeq::new("main".to_string())
+        .parse_corpus(&Corpus::new(tokens), &refs)
+        .unwrap_err();
+    assert_eq!((err.file, err.span), (include, 1..1));
+}
+
+#[test]
+pub fn json_string_test() {
+    assert_eq!(json_string("a\"b\\\n\u{1}"), "\"a\\\"b\\\\\\n\\u0001\"");
+}
@@ -0,0 +1,75 @@
+use crate::corpus::*;
+use crate::sequence::*;
+
+// The signature of every rule in a parser generated by
+// `codegen::generate_parser`.
+pub type RuleFn = for<'a> fn(&[Token<'a>], usize, &mut ParseContext<'a>) -> Option<TokenMatch<'a>>;
+
+// A generated rule, usable wherever a `Sequence` is expected or on its own.
+// In a `RefMap` it can go under any name; errors name the generated rule.
+#[derive(Clone, Copy)]
+pub struct CompiledSeq(pub RuleFn);
+
+impl Sequence for CompiledSeq {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        (self.0)(tokens, pos, ctx)
+    }
+
+    fn is_rule(&self) -> bool {
+        true
+    }
+}
+
+impl CompiledSeq {
+    pub fn match_first<'a>(&self, corpus: &Corpus<'a>) -> Option<TokenMatch<'a>> {
+        (self.0)(&corpus.tokens, 0, &mut ParseContext::without_refs())
+    }
+
+    pub fn parse<'a>(&self, corpus: &Corpus<'a>) -> Result<Token<'a>, ParseError> {
+        self.parse_with(corpus, &mut ParseContext::without_refs())
+    }
+
+    pub fn parse_with<'a>(
+        &self,
+        corpus: &Corpus<'a>,
+        ctx: &mut ParseContext<'a>,
+    ) -> Result<Token<'a>, ParseError> {
+        let matched = (self.0)(&corpus.tokens, 0, ctx);
+        complete_match(matched, &corpus.tokens, ctx)
+    }
+
+    pub fn parse_recovering<'a>(&self, corpus: &Corpus<'a>) -> Recovered<'a> {
+        let mut ctx = ParseContext::without_refs().with_recovery();
+        let matched = (self.0)(&corpus.tokens, 0, &mut ctx);
+        recover_match(matched, &corpus.tokens, &mut ctx)
+    }
+}
+
+// The rules of a generated parser by name, as produced by the `grammar!` and
+// `include_grammar!` macros of `tuckey-macros`.
+#[derive(Clone, Copy)]
+pub struct Grammar {
+    rules: &'static [(&'static str, RuleFn)],
+}
+
+impl Grammar {
+    pub const fn new(rules: &'static [(&'static str, RuleFn)]) -> Self {
+        Self { rules }
+    }
+
+    pub fn rules(&self) -> &'static [(&'static str, RuleFn)] {
+        self.rules
+    }
+
+    pub fn rule(&self, name: &str) -> Option<CompiledSeq> {
+        self.rules
+            .iter()
+            .find(|(rule, _)| *rule == name)
+            .map(|(_, rule)| CompiledSeq(*rule))
+    }
+}
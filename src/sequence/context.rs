@@ -0,0 +1,473 @@
+use std::any::Any;
+use std::collections::HashMap;
+
+use crate::ast::AstError;
+use crate::sequence::*;
+
+pub struct ParseContext<'a> {
+    refs: Option<&'a RefMap>,
+    pub rules: Vec<&'a str>,
+    furthest: Option<usize>,
+    expected: Vec<Expected>,
+    chain: Vec<&'a str>,
+    silent: usize,
+    peeking: usize,
+    memo: Option<Memo<'a>>,
+    seeds: HashMap<(&'a str, usize), Seed<'a>>,
+    growing: usize,
+    actions: Option<&'a dyn RuleActions>,
+    values: Vec<ActionValue<'a>>,
+    action_error: Option<AstError>,
+    recovery: bool,
+    cut_error: Option<ParseError>,
+}
+
+// Values made by actions, each labelled with the nearest name above it.
+pub type LabelledValues<'t> = Vec<(&'t str, Box<dyn Any>)>;
+
+// Runs code when a rule finishes matching, turning its token into a value
+// that is kept on the context. Implemented by `action::Actions`.
+pub trait RuleActions {
+    fn handles(&self, rule: &str) -> bool;
+
+    // `values` are those of the rules with actions inside `token`.
+    fn run<'t>(
+        &self,
+        rule: &str,
+        token: &'t Token<'_>,
+        values: LabelledValues<'t>,
+    ) -> Result<Box<dyn Any>, AstError>;
+}
+
+// `value` is gone once the action of an enclosing rule has taken it.
+// `inner` is the value of a rule that this one only passed on, as in
+// `a = b`, which shares its token.
+struct ActionValue<'a> {
+    rule: &'a str,
+    inner: Option<usize>,
+    value: Option<Box<dyn Any>>,
+}
+
+// The result a left-recursive rule has grown to so far at one position.
+#[derive(Default)]
+struct Seed<'a> {
+    matched: Option<TokenMatch<'a>>,
+    recursed: bool,
+    growing: bool,
+}
+
+#[derive(Clone, Copy)]
+pub struct FailureSnapshot {
+    furthest: Option<usize>,
+    expected_len: usize,
+}
+
+impl<'a> ParseContext<'a> {
+    pub fn new(refs: &'a RefMap) -> Self {
+        Self {
+            refs: Some(refs),
+            ..Self::without_refs()
+        }
+    }
+
+    // For compiled parsers, whose rules call each other directly.
+    pub fn without_refs() -> Self {
+        Self {
+            refs: None,
+            rules: vec![],
+            furthest: None,
+            expected: vec![],
+            chain: vec![],
+            silent: 0,
+            peeking: 0,
+            memo: None,
+            seeds: HashMap::new(),
+            growing: 0,
+            actions: None,
+            values: vec![],
+            action_error: None,
+            recovery: false,
+            cut_error: None,
+        }
+    }
+
+    pub fn refs(&self) -> Option<&'a RefMap> {
+        self.refs
+    }
+
+    pub fn with_memo(mut self, options: MemoOptions) -> Self {
+        self.memo = Some(Memo::new(options));
+        self
+    }
+
+    // Runs `actions` as rules finish, except inside lookahead, whose matches
+    // are never kept.
+    pub fn with_actions(mut self, actions: &'a dyn RuleActions) -> Self {
+        self.actions = Some(actions);
+        self
+    }
+
+    // Lets `RecoverSeq`s skip input they cannot match instead of failing.
+    pub fn with_recovery(mut self) -> Self {
+        self.recovery = true;
+        self
+    }
+
+    // Lookahead never recovers: it only asks whether something matches.
+    pub fn recovering(&self) -> bool {
+        self.recovery && self.silent == 0 && self.peeking == 0
+    }
+
+    pub fn memo(&self) -> Option<&Memo<'a>> {
+        self.memo.as_ref()
+    }
+
+    // Failures recorded while silent are never reported, so results computed
+    // in that mode are kept out of the table.
+    pub fn memo_get(&mut self, rule: &str, pos: usize) -> Option<Option<TokenMatch<'a>>> {
+        match &mut self.memo {
+            Some(memo) if self.silent == 0 && memo.applies_to(rule) => memo.get(rule, pos),
+            _ => None,
+        }
+    }
+
+    // Results computed while a seed is growing may depend on that seed, those
+    // after a failed cut were cut short, and cuts and recovery are off during
+    // lookahead, so none of these are cached either.
+    pub fn memo_insert(&mut self, rule: &'a str, pos: usize, matched: &Option<TokenMatch<'a>>) {
+        let failed = self.cut_failed();
+        match &mut self.memo {
+            Some(memo)
+                if self.silent == 0
+                    && self.peeking == 0
+                    && self.growing == 0
+                    && !failed
+                    && memo.applies_to(rule) =>
+            {
+                memo.insert(rule, pos, matched.clone())
+            }
+            _ => {}
+        }
+    }
+
+    pub fn furthest(&self) -> Option<usize> {
+        self.furthest
+    }
+
+    pub fn fail(&mut self, pos: usize, expected: Expected) {
+        if self.silent > 0 {
+            return;
+        }
+        match self.furthest {
+            Some(furthest) if furthest > pos => {}
+            Some(furthest) if furthest == pos => self.expected.push(expected),
+            _ => {
+                self.furthest = Some(pos);
+                self.expected = vec![expected];
+                self.chain = self.rules.clone();
+            }
+        }
+    }
+
+    pub fn silently<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.silent += 1;
+        let result = f(self);
+        self.silent -= 1;
+        result
+    }
+
+    // For positive lookahead: failures are reported as usual, but nothing
+    // inside recovers or commits.
+    pub fn peek<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.peeking += 1;
+        let result = f(self);
+        self.peeking -= 1;
+        result
+    }
+
+    pub fn seed(&mut self, rule: &'a str, pos: usize) -> Option<Option<TokenMatch<'a>>> {
+        let seed = self.seeds.get_mut(&(rule, pos))?;
+        seed.recursed = true;
+        if !seed.growing {
+            seed.growing = true;
+            self.growing += 1;
+        }
+        Some(seed.matched.clone())
+    }
+
+    // Runs `f` as the body of the rule `rule` at `pos`: answers from a
+    // growing seed or the memo table where possible, tracks the rule for
+    // error messages and grows left-recursive matches.
+    pub fn call_rule(
+        &mut self,
+        rule: &'a str,
+        pos: usize,
+        mut f: impl FnMut(&mut Self) -> Option<TokenMatch<'a>>,
+    ) -> Option<TokenMatch<'a>> {
+        if let Some(matched) = self.seed(rule, pos) {
+            return matched;
+        }
+        if let Some(matched) = self.memo_get(rule, pos) {
+            if matched.is_none() {
+                self.fail(pos, Expected::Rule(rule.to_string()));
+            }
+            return matched;
+        }
+        let snapshot = self.snapshot();
+        self.rules.push(rule);
+        // Naming and acting inside `grow` covers left-recursive seeds too.
+        let matched = self.grow(rule, pos, |ctx| {
+            let matched = rule_match(rule, f(ctx)?);
+            ctx.act(rule, matched)
+        });
+        self.rules.pop();
+        self.collapse(snapshot, pos, rule);
+        self.memo_insert(rule, pos, &matched);
+        matched
+    }
+
+    // `call_rule` for a rule that can never call itself at the position it
+    // started at, so that there is no seed to look up or grow. Without a memo
+    // table, that leaves only the error tracking and the rule's action.
+    pub fn call_plain_rule(
+        &mut self,
+        rule: &'a str,
+        pos: usize,
+        mut f: impl FnMut(&mut Self) -> Option<TokenMatch<'a>>,
+    ) -> Option<TokenMatch<'a>> {
+        if self.memo.is_some() {
+            return self.call_rule(rule, pos, f);
+        }
+        let snapshot = self.snapshot();
+        self.rules.push(rule);
+        let matched = f(self).and_then(|matched| self.act(rule, rule_match(rule, matched)));
+        self.rules.pop();
+        self.collapse(snapshot, pos, rule);
+        matched
+    }
+
+    // Matches `f` as the rule `rule`, growing the result from a failed seed
+    // while the rule keeps calling itself at `pos` and each pass consumes more.
+    pub fn grow(
+        &mut self,
+        rule: &'a str,
+        pos: usize,
+        mut f: impl FnMut(&mut Self) -> Option<TokenMatch<'a>>,
+    ) -> Option<TokenMatch<'a>> {
+        let key = (rule, pos);
+        self.seeds.insert(key, Seed::default());
+        let mut matched = f(self);
+        while self.seeds[&key].recursed {
+            if self.cut_failed() {
+                matched = None;
+                break;
+            }
+            let seed = self.seeds.get_mut(&key).unwrap();
+            let longer = matched
+                .as_ref()
+                .is_some_and(|m| seed.matched.as_ref().is_none_or(|s| m.len > s.len));
+            if !longer {
+                matched = seed.matched.take();
+                break;
+            }
+            seed.matched = matched;
+            seed.recursed = false;
+            matched = f(self);
+        }
+        if self.seeds.remove(&key).is_some_and(|seed| seed.growing) {
+            self.growing -= 1;
+        }
+        matched
+    }
+
+    // Runs `f` with failures tracked apart from the rest of the parse, so
+    // that if it fails the error describes only what `f` tried. Failures from
+    // a successful `f` are kept as usual.
+    pub fn attempt<T>(
+        &mut self,
+        tokens: &[Token<'_>],
+        f: impl FnOnce(&mut Self) -> Option<T>,
+    ) -> Result<T, ParseError> {
+        let furthest = self.furthest.take();
+        let expected = std::mem::take(&mut self.expected);
+        let chain = std::mem::take(&mut self.chain);
+        let matched = f(self);
+        let Some(matched) = matched else {
+            let error = self.error(tokens);
+            (self.furthest, self.expected, self.chain) = (furthest, expected, chain);
+            return Err(error);
+        };
+        match (furthest, self.furthest) {
+            (Some(before), Some(now)) if before == now => {
+                self.expected.splice(0..0, expected);
+            }
+            (Some(before), now) if now.is_none_or(|now| before > now) => {
+                (self.furthest, self.expected, self.chain) = (furthest, expected, chain);
+            }
+            _ => {}
+        }
+        Ok(matched)
+    }
+
+    // Runs `f`, the part of a sequence after a cut. If it fails, the parse
+    // is over: choices and repetitions above stop rather than try something
+    // else, and the error is the one from inside `f`. Lookahead is only
+    // asking a question, so cuts inside it fail as usual.
+    pub fn commit<T>(
+        &mut self,
+        tokens: &[Token<'_>],
+        f: impl FnOnce(&mut Self) -> Option<T>,
+    ) -> Option<T> {
+        if self.silent > 0 || self.peeking > 0 {
+            return f(self);
+        }
+        match self.attempt(tokens, f) {
+            Ok(matched) => Some(matched),
+            Err(error) => {
+                self.cut_error.get_or_insert(error);
+                None
+            }
+        }
+    }
+
+    // Also true once an action has failed, which ends the parse the same way.
+    pub fn cut_failed(&self) -> bool {
+        self.cut_error.is_some() || self.action_error.is_some()
+    }
+
+    pub fn take_action_error(&mut self) -> Option<AstError> {
+        self.action_error.take()
+    }
+
+    // Runs the action for `rule`, if any, on its match and keeps the value.
+    // A failed action fails the match and stops the parse.
+    fn act(&mut self, rule: &'a str, mut matched: TokenMatch<'a>) -> Option<TokenMatch<'a>> {
+        let Some(actions) = self.actions.filter(|actions| actions.handles(rule)) else {
+            return Some(matched);
+        };
+        if self.silent > 0 || self.peeking > 0 {
+            return Some(matched);
+        }
+        let inner = matched.new_token.value.take();
+        match self.run_action(actions, rule, inner, &matched.new_token) {
+            Ok(value) => {
+                matched.new_token.value = Some(self.values.len());
+                self.values.push(ActionValue {
+                    rule,
+                    inner,
+                    value: Some(value),
+                });
+                Some(matched)
+            }
+            Err(error) => {
+                self.action_error.get_or_insert(error);
+                None
+            }
+        }
+    }
+
+    fn run_action(
+        &mut self,
+        actions: &dyn RuleActions,
+        rule: &str,
+        inner: Option<usize>,
+        token: &Token<'a>,
+    ) -> Result<Box<dyn Any>, AstError> {
+        let mut values = vec![];
+        match inner {
+            Some(index) => values.push(("", self.take_value(index, token)?)),
+            None => self.collect_values(token, &mut values)?,
+        }
+        actions.run(rule, token, values)
+    }
+
+    // The values under `token`, with no name yet for those not under one.
+    pub fn take_values<'t>(
+        &mut self,
+        token: &'t Token<'a>,
+    ) -> Result<LabelledValues<'t>, AstError> {
+        let mut values = vec![];
+        match token.value {
+            Some(index) => values.push(("", self.take_value(index, token)?)),
+            None => self.collect_values(token, &mut values)?,
+        }
+        Ok(values)
+    }
+
+    fn collect_values<'t>(
+        &mut self,
+        token: &'t Token<'a>,
+        values: &mut LabelledValues<'t>,
+    ) -> Result<(), AstError> {
+        for (name, child) in token.entries() {
+            let start = values.len();
+            match child.value {
+                Some(index) => values.push(("", self.take_value(index, child)?)),
+                None => self.collect_values(child, values)?,
+            }
+            for (label, _) in &mut values[start..] {
+                if label.is_empty() {
+                    *label = name;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // A value can already be gone when its token is reused from the memo
+    // table or a left-recursive seed after the match that took it was
+    // dropped. Actions only see their token and values, so running them
+    // again gives the same value.
+    fn take_value(&mut self, index: usize, token: &Token<'a>) -> Result<Box<dyn Any>, AstError> {
+        if let Some(value) = self.values[index].value.take() {
+            return Ok(value);
+        }
+        let ActionValue { rule, inner, .. } = self.values[index];
+        let actions = self.actions.expect("values come from actions");
+        self.run_action(actions, rule, inner, token)
+    }
+
+    // Lets the parse go on after a failed cut, for recovery.
+    pub fn take_cut_error(&mut self) -> Option<ParseError> {
+        self.cut_error.take()
+    }
+
+    pub fn snapshot(&self) -> FailureSnapshot {
+        FailureSnapshot {
+            furthest: self.furthest,
+            expected_len: self.expected.len(),
+        }
+    }
+
+    // If a rule made no progress past `pos`, report the rule itself rather than
+    // everything it tried internally.
+    pub fn collapse(&mut self, snapshot: FailureSnapshot, pos: usize, rule: &str) {
+        let untouched =
+            snapshot.furthest == self.furthest && snapshot.expected_len == self.expected.len();
+        if self.silent > 0 || untouched || self.furthest != Some(pos) {
+            return;
+        }
+        if snapshot.furthest == Some(pos) {
+            self.expected.truncate(snapshot.expected_len);
+        } else {
+            self.expected.clear();
+            self.chain = self.rules.clone();
+        }
+        self.expected.push(Expected::Rule(rule.to_string()));
+    }
+
+    pub fn error(&self, tokens: &[Token<'_>]) -> ParseError {
+        if let Some(error) = &self.cut_error {
+            return error.clone();
+        }
+        let mut expected = self.expected.clone();
+        expected.sort();
+        expected.dedup();
+        ParseError::new(
+            tokens,
+            self.furthest.unwrap_or(0),
+            expected,
+            self.chain.iter().map(|rule| rule.to_string()).collect(),
+        )
+    }
+}
@@ -0,0 +1,105 @@
+use core::ops::Range;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::source::*;
+use crate::token::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Expected {
+    Literal(String),
+    Range(u32, u32),
+    Rule(String),
+    Any,
+    Predicate,
+    Except,
+    End,
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show =
+            |c: &u32| char::from_u32(*c).map_or_else(|| format!("{c:#x}"), |c| c.to_string());
+        match self {
+            Expected::Literal(target) => write!(f, "`{target}`"),
+            Expected::Range(start, end) => write!(f, "`{}`..`{}`", show(start), show(end)),
+            Expected::Rule(name) => write!(f, "{name}"),
+            Expected::Any => write!(f, "any token"),
+            Expected::Predicate => write!(f, "a matching token"),
+            Expected::Except => write!(f, "a different token"),
+            Expected::End => write!(f, "end of input"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub index: usize,
+    // The file of the token the error points at, which `span`, `line` and
+    // `column` are relative to.
+    pub file: FileId,
+    pub span: Range<usize>,
+    pub line: usize,
+    pub column: usize,
+    pub expected: Vec<Expected>,
+    pub rules: Vec<String>,
+}
+
+impl ParseError {
+    pub fn new(
+        tokens: &[Token<'_>],
+        index: usize,
+        expected: Vec<Expected>,
+        rules: Vec<String>,
+    ) -> Self {
+        let (source, file, span) = match tokens.get(index) {
+            Some(t) => (
+                t.source,
+                t.file,
+                t.content_range().unwrap_or(t.source.len()..t.source.len()),
+            ),
+            None => {
+                let source = tokens.last().map_or("", |t| t.source);
+                let file = tokens.last().map_or(FileId::default(), |t| t.file);
+                let end = tokens
+                    .last()
+                    .and_then(|t| t.content_range())
+                    .map_or(source.len(), |r| r.end);
+                (source, file, end..end)
+            }
+        };
+        let (line, column) = line_col(source, span.start);
+        Self {
+            index,
+            file,
+            span,
+            line,
+            column,
+            expected,
+            rules,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self.expected.as_slice() {
+            [] => "unexpected input".to_string(),
+            [one] => format!("expected {one}"),
+            many => {
+                let many: Vec<String> = many.iter().map(|e| e.to_string()).collect();
+                format!("expected one of {}", many.join(", "))
+            }
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}:{}", self.message(), self.line, self.column)?;
+        if !self.rules.is_empty() {
+            write!(f, " while parsing {}", self.rules.join(" > "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {}
@@ -0,0 +1,79 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::sequence::*;
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoOptions {
+    pub rules: Option<HashSet<String>>,
+    pub capacity: Option<usize>,
+}
+
+impl MemoOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn only<S: Into<String>>(rules: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            rules: Some(rules.into_iter().map(Into::into).collect()),
+            capacity: None,
+        }
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+}
+
+pub struct Memo<'a> {
+    pub options: MemoOptions,
+    pub hits: usize,
+    pub misses: usize,
+    table: HashMap<(&'a str, usize), Option<TokenMatch<'a>>>,
+}
+
+impl<'a> Memo<'a> {
+    pub fn new(options: MemoOptions) -> Self {
+        Self {
+            options,
+            hits: 0,
+            misses: 0,
+            table: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    pub fn applies_to(&self, rule: &str) -> bool {
+        self.options
+            .rules
+            .as_ref()
+            .is_none_or(|rules| rules.contains(rule))
+    }
+
+    pub fn get(&mut self, rule: &str, pos: usize) -> Option<Option<TokenMatch<'a>>> {
+        let found = self.table.get(&(rule, pos)).cloned();
+        match found {
+            Some(_) => self.hits += 1,
+            None => self.misses += 1,
+        }
+        found
+    }
+
+    pub fn insert(&mut self, rule: &'a str, pos: usize, matched: Option<TokenMatch<'a>>) {
+        if self
+            .options
+            .capacity
+            .is_none_or(|capacity| self.table.len() < capacity)
+        {
+            self.table.insert((rule, pos), matched);
+        }
+    }
+}
@@ -1,11 +1,23 @@
 use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::corpus::*;
+use crate::report::*;
+use crate::source::*;
 use crate::token::*;
 
+mod compiled;
+mod context;
+mod error;
+mod memo;
 #[cfg(test)]
 mod tests;
 
+pub use compiled::*;
+pub use context::*;
+pub use error::*;
+pub use memo::*;
+
 #[derive(Debug, Clone)]
 pub struct TokenMatch<'a> {
     pub len: usize,
@@ -14,6 +26,14 @@ pub struct TokenMatch<'a> {
 
 pub type RefMap = HashMap<String, Box<dyn Sequence>>;
 
+// A tree for the whole input, however broken, with the errors recovered from
+// while building it.
+#[derive(Debug, Clone)]
+pub struct Recovered<'a> {
+    pub token: Token<'a>,
+    pub errors: Vec<ParseError>,
+}
+
 pub enum TokenMatchTestType {
     None,
     First,
@@ -21,32 +41,102 @@ pub enum TokenMatchTestType {
 }
 
 pub trait Sequence {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], refs: &'a RefMap) -> Option<TokenMatch>;
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>>;
+
+    // Whether `match_at` already runs as a rule through
+    // `ParseContext::call_rule`, as generated rules do. A `RefSeq` calls such
+    // sequences directly rather than tracking them a second time.
+    fn is_rule(&self) -> bool {
+        false
+    }
+
+    fn match_tokens<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        refs: &'a RefMap,
+    ) -> Option<TokenMatch<'a>> {
+        self.match_at(tokens, 0, &mut ParseContext::new(refs))
+    }
 
     fn match_corpus_first<'a>(
         &'a self,
         corpus: &Corpus<'a>,
         refs: &'a RefMap,
-    ) -> Option<TokenMatch> {
+    ) -> Option<TokenMatch<'a>> {
         self.match_tokens(&corpus.tokens, refs)
     }
 
+    fn parse_tokens<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        refs: &'a RefMap,
+    ) -> Result<TokenMatch<'a>, ParseError> {
+        let mut ctx = ParseContext::new(refs);
+        self.match_at(tokens, 0, &mut ctx)
+            .filter(|_| !ctx.cut_failed())
+            .ok_or_else(|| ctx.error(tokens))
+    }
+
+    fn parse_corpus<'a>(
+        &'a self,
+        corpus: &Corpus<'a>,
+        refs: &'a RefMap,
+    ) -> Result<Token<'a>, ParseError> {
+        self.parse_corpus_with(corpus, &mut ParseContext::new(refs))
+    }
+
+    fn parse_corpus_with<'a>(
+        &'a self,
+        corpus: &Corpus<'a>,
+        ctx: &mut ParseContext<'a>,
+    ) -> Result<Token<'a>, ParseError> {
+        let matched = self.match_at(&corpus.tokens, 0, ctx);
+        complete_match(matched, &corpus.tokens, ctx)
+    }
+
+    // Parses with recovery on, so that `RecoverSeq`s skip what they cannot
+    // match. Whatever is still left over at the end becomes one last error
+    // node.
+    fn parse_recovering<'a>(&'a self, corpus: &Corpus<'a>, refs: &'a RefMap) -> Recovered<'a> {
+        let mut ctx = ParseContext::new(refs).with_recovery();
+        let matched = self.match_at(&corpus.tokens, 0, &mut ctx);
+        recover_match(matched, &corpus.tokens, &mut ctx)
+    }
+
     fn assert_matches<'a>(
         &'a self,
         corpus: &Corpus<'a>,
         refs: &'a RefMap,
         should_match: TokenMatchTestType,
     ) {
-        let matched = dbg!(self.match_corpus_first(corpus, refs));
+        let mut ctx = ParseContext::new(refs);
+        let matched = self.match_at(&corpus.tokens, 0, &mut ctx);
+        let source = corpus.tokens.first().map_or("", |t| t.source);
         match should_match {
             TokenMatchTestType::None => {
-                assert!(matched.is_none());
+                assert!(matched.is_none(), "expected no match, got {matched:?}");
             }
             TokenMatchTestType::First => {
-                assert!(matched.is_some());
+                assert!(
+                    matched.is_some(),
+                    "\n{}",
+                    Diagnostic::from(&ctx.error(&corpus.tokens)).render(source, RenderStyle::Plain)
+                );
             }
             TokenMatchTestType::All => {
-                assert!(matched.is_some_and(|t| t.len == corpus.tokens.len()));
+                if let Some(matched) = &matched {
+                    ctx.fail(matched.len, Expected::End);
+                }
+                assert!(
+                    matched.is_some_and(|t| t.len == corpus.tokens.len()),
+                    "\n{}",
+                    Diagnostic::from(&ctx.error(&corpus.tokens)).render(source, RenderStyle::Plain)
+                );
             }
         }
     }
@@ -58,17 +148,13 @@ pub struct RawSeq {
 }
 
 impl Sequence for RawSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], _: &'a RefMap) -> Option<TokenMatch> {
-        tokens.first().and_then(move |t| {
-            if t.content() == self.target {
-                Some(TokenMatch {
-                    len: 1,
-                    new_token: t.clone(),
-                })
-            } else {
-                None
-            }
-        })
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match_raw(&self.target, tokens, pos, ctx)
     }
 }
 
@@ -83,24 +169,15 @@ pub struct MultSeq {
 }
 
 impl Sequence for MultSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], refs: &'a RefMap) -> Option<TokenMatch> {
-        let mut match_index = 0;
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
         let mut children = vec![];
-        for (seq, key) in &self.seqs {
-            if let Some(matched) = seq.match_tokens(&tokens[match_index..], refs) {
-                children.push((key.to_string(), matched.new_token));
-                match_index += matched.len;
-            } else {
-                return None;
-            }
-        }
-        Some(TokenMatch {
-            len: match_index,
-            new_token: Token {
-                source: tokens[0].source,
-                data: TokenData::Branch(children),
-            },
-        })
+        let len = match_each(&self.seqs, tokens, pos, ctx, &mut children)?;
+        Some(branch_at(tokens, pos, len, children))
     }
 }
 
@@ -110,26 +187,90 @@ impl MultSeq {
     }
 }
 
+// Matches each of `seqs` in turn after `pos`, adding them to `children`, and
+// returns how many tokens they took.
+fn match_each<'a>(
+    seqs: &'a [(Box<dyn Sequence>, String)],
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+    children: &mut Vec<(String, Token<'a>)>,
+) -> Option<usize> {
+    let mut len = 0;
+    for (seq, key) in seqs {
+        let matched = seq.match_at(tokens, pos + len, ctx)?;
+        children.push((key.to_string(), matched.new_token));
+        len += matched.len;
+    }
+    Some(len)
+}
+
+// A `MultSeq` that commits to its match once the first `cut` sequences have
+// matched: see `ParseContext::commit`.
+pub struct CutSeq {
+    pub seqs: Vec<(Box<dyn Sequence>, String)>,
+    pub cut: usize,
+}
+
+impl Sequence for CutSeq {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        let (before, after) = self.seqs.split_at(self.cut);
+        let mut children = vec![];
+        let len = match_each(before, tokens, pos, ctx, &mut children)?;
+        let rest = ctx.commit(tokens, |ctx| {
+            match_each(after, tokens, pos + len, ctx, &mut children)
+        })?;
+        Some(branch_at(tokens, pos, len + rest, children))
+    }
+}
+
+impl CutSeq {
+    pub fn new(seqs: Vec<(Box<dyn Sequence>, String)>, cut: usize) -> Self {
+        Self { seqs, cut }
+    }
+}
+
 pub struct OptSeq {
     pub seq: Box<dyn Sequence>,
     pub match_name: String,
 }
 
 impl Sequence for OptSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], refs: &'a RefMap) -> Option<TokenMatch> {
-        match self.seq.match_tokens(tokens, refs) {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match self.seq.match_at(tokens, pos, ctx) {
             Some(did_match) => Some(TokenMatch {
                 len: did_match.len,
                 new_token: Token {
-                    source: tokens[0].source,
-                    data: TokenData::Branch(vec![(self.match_name.clone(), did_match.new_token)]),
+                    source: source_at(tokens, pos),
+                    file: file_at(tokens, pos),
+                    rule: None,
+                    alt: None,
+                    value: None,
+                    data: TokenData::Branch(
+                        vec![(self.match_name.clone(), did_match.new_token)].into(),
+                    ),
                 },
             }),
+            None if ctx.cut_failed() => None,
             None => Some(TokenMatch {
                 len: 0,
                 new_token: Token {
-                    source: tokens[0].source,
-                    data: TokenData::Branch(vec![]),
+                    source: source_at(tokens, pos),
+                    file: file_at(tokens, pos),
+                    rule: None,
+                    alt: None,
+                    value: None,
+                    data: TokenData::Branch(vec![].into()),
                 },
             }),
         }
@@ -148,18 +289,34 @@ pub struct NoneOrMoreSeq {
 }
 
 impl Sequence for NoneOrMoreSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], refs: &'a RefMap) -> Option<TokenMatch> {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
         let mut match_index = 0;
         let mut children = vec![];
-        while let Some(matched) = self.seq.match_tokens(&tokens[match_index..], refs) {
+        while let Some(matched) = self.seq.match_at(tokens, pos + match_index, ctx) {
             children.push((self.match_name.to_string(), matched.new_token));
+            // An empty match would repeat forever at the same position.
+            if matched.len == 0 {
+                break;
+            }
             match_index += matched.len;
         }
+        if ctx.cut_failed() {
+            return None;
+        }
         Some(TokenMatch {
             len: match_index,
             new_token: Token {
-                source: tokens.get(0).map_or_else(|| "", |t| t.source),
-                data: TokenData::Branch(children),
+                source: source_at(tokens, pos),
+                file: file_at(tokens, pos),
+                rule: None,
+                alt: None,
+                value: None,
+                data: TokenData::Branch(children.into()),
             },
         })
     }
@@ -177,19 +334,32 @@ pub struct OneOrMoreSeq {
 }
 
 impl Sequence for OneOrMoreSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], refs: &'a RefMap) -> Option<TokenMatch> {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
         let mut match_index = 0;
         let mut children = vec![];
-        while let Some(matched) = self.seq.match_tokens(&tokens[match_index..], refs) {
+        while let Some(matched) = self.seq.match_at(tokens, pos + match_index, ctx) {
             children.push((self.match_name.clone(), matched.new_token));
+            // An empty match would repeat forever at the same position.
+            if matched.len == 0 {
+                break;
+            }
             match_index += matched.len;
         }
-        if match_index > 0 {
+        if !children.is_empty() && !ctx.cut_failed() {
             Some(TokenMatch {
                 len: match_index,
                 new_token: Token {
-                    source: tokens.get(0).map_or_else(|| "", |t| t.source),
-                    data: TokenData::Branch(children),
+                    source: source_at(tokens, pos),
+                    file: file_at(tokens, pos),
+                    rule: None,
+                    alt: None,
+                    value: None,
+                    data: TokenData::Branch(children.into()),
                 },
             })
         } else {
@@ -204,21 +374,113 @@ impl OneOrMoreSeq {
     }
 }
 
+// Between `min` and `max` matches of `seq`, or at least `min` without a
+// `max`, as children named `match_name`.
+pub struct RepeatSeq {
+    pub match_name: String,
+    pub seq: Box<dyn Sequence>,
+    pub min: usize,
+    pub max: Option<usize>,
+}
+
+impl Sequence for RepeatSeq {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match_repeat(
+            tokens,
+            pos,
+            ctx,
+            &self.match_name,
+            self.min,
+            self.max,
+            |ctx, at| self.seq.match_at(tokens, at, ctx),
+        )
+    }
+}
+
+impl RepeatSeq {
+    pub fn new(seq: Box<dyn Sequence>, match_name: String, min: usize, max: Option<usize>) -> Self {
+        Self {
+            match_name,
+            seq,
+            min,
+            max,
+        }
+    }
+}
+
+// Matches of `seq` separated by matches of `sep`, as a flat list of children.
+pub struct SepSeq {
+    pub seq: Box<dyn Sequence>,
+    pub sep: Box<dyn Sequence>,
+    pub item_name: String,
+    pub sep_name: Option<String>,
+    pub min: usize,
+    pub trailing: bool,
+}
+
+impl Sequence for SepSeq {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match_separated(
+            tokens,
+            pos,
+            ctx,
+            &self.item_name,
+            self.sep_name.as_deref(),
+            self.min,
+            self.trailing,
+            |ctx, at| self.seq.match_at(tokens, at, ctx),
+            |ctx, at| self.sep.match_at(tokens, at, ctx),
+        )
+    }
+}
+
+impl SepSeq {
+    pub fn new(
+        seq: Box<dyn Sequence>,
+        item_name: String,
+        sep: Box<dyn Sequence>,
+        sep_name: Option<String>,
+        min: usize,
+        trailing: bool,
+    ) -> Self {
+        Self {
+            seq,
+            sep,
+            item_name,
+            sep_name,
+            min,
+            trailing,
+        }
+    }
+}
+
 pub struct ChooseSeq {
     pub seqs: Vec<(Box<dyn Sequence>, String)>,
 }
 
 impl Sequence for ChooseSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], refs: &'a RefMap) -> Option<TokenMatch> {
-        for (seq, name) in &self.seqs {
-            if let Some(matched) = seq.match_tokens(tokens, refs) {
-                return Some(TokenMatch {
-                    len: matched.len,
-                    new_token: Token {
-                        source: matched.new_token.source,
-                        data: TokenData::Branch(vec![(name.to_string(), matched.new_token)]),
-                    },
-                });
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        for (alt, (seq, name)) in self.seqs.iter().enumerate() {
+            if let Some(matched) = seq.match_at(tokens, pos, ctx) {
+                return Some(choose_match(alt, name, matched));
+            }
+            if ctx.cut_failed() {
+                return None;
             }
         }
         None
@@ -233,7 +495,7 @@ impl ChooseSeq {
     pub fn from_chars(chars: &str) -> Self {
         Self {
             seqs: chars
-                .chars()
+                .graphemes(true)
                 .map(|c| {
                     (
                         Box::new(RawSeq::new(c.to_string())) as Box<dyn Sequence>,
@@ -250,9 +512,20 @@ pub struct RefSeq {
 }
 
 impl Sequence for RefSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], refs: &'a RefMap) -> Option<TokenMatch> {
-        refs.get(&self.name)
-            .and_then(|s| s.match_tokens(tokens, refs))
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        let Some(seq) = ctx.refs().and_then(|refs| refs.get(&self.name)) else {
+            ctx.fail(pos, Expected::Rule(self.name.clone()));
+            return None;
+        };
+        if seq.is_rule() {
+            return seq.match_at(tokens, pos, ctx);
+        }
+        ctx.call_rule(&self.name, pos, |ctx| seq.match_at(tokens, pos, ctx))
     }
 }
 
@@ -266,15 +539,13 @@ impl RefSeq {
 pub struct AnySeq {}
 
 impl Sequence for AnySeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], _: &'a RefMap) -> Option<TokenMatch> {
-        if !tokens.is_empty() {
-            Some(TokenMatch {
-                len: 1,
-                new_token: tokens[0].clone(),
-            })
-        } else {
-            None
-        }
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match_any(tokens, pos, ctx)
     }
 }
 
@@ -295,17 +566,22 @@ pub struct WhereSeq {
 }
 
 impl Sequence for WhereSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], _: &'a RefMap) -> Option<TokenMatch> {
-        tokens.first().and_then(move |t| {
-            if (self.predicate)(t) {
-                Some(TokenMatch {
-                    len: 1,
-                    new_token: t.clone(),
-                })
-            } else {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match tokens.get(pos) {
+            Some(t) if (self.predicate)(t) => Some(TokenMatch {
+                len: 1,
+                new_token: t.clone(),
+            }),
+            _ => {
+                ctx.fail(pos, Expected::Predicate);
                 None
             }
-        })
+        }
     }
 }
 
@@ -322,18 +598,13 @@ pub struct RangeSeq {
 }
 
 impl Sequence for RangeSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], _: &'a RefMap) -> Option<TokenMatch> {
-        tokens.first().and_then(move |t| {
-            let first_char = t.content().chars().next().unwrap() as u32;
-            if (self.start..=self.end).contains(&first_char) {
-                Some(TokenMatch {
-                    len: 1,
-                    new_token: t.clone(),
-                })
-            } else {
-                None
-            }
-        })
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match_range(self.start, self.end, tokens, pos, ctx)
     }
 }
 
@@ -347,25 +618,13 @@ impl RangeSeq {
 pub struct WhitespaceSeq {}
 
 impl Sequence for WhitespaceSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], _: &'a RefMap) -> Option<TokenMatch> {
-        let mut match_index = 0;
-        let mut children = vec![];
-        while !tokens[match_index..].is_empty()
-            && tokens[match_index]
-                .content()
-                .chars()
-                .all(|c| c.is_whitespace())
-        {
-            children.push(("".to_string(), tokens[0].clone()));
-            match_index += 1;
-        }
-        Some(TokenMatch {
-            len: match_index,
-            new_token: Token {
-                source: tokens.get(0).map_or_else(|| "", |t| t.source),
-                data: TokenData::Branch(children),
-            },
-        })
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        _: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        Some(match_whitespace(tokens, pos))
     }
 }
 
@@ -385,14 +644,13 @@ impl Default for WhitespaceSeq {
 pub struct NilSeq {}
 
 impl Sequence for NilSeq {
-    fn match_tokens<'a>(&'a self, _: &[Token<'a>], _: &'a RefMap) -> Option<TokenMatch> {
-        Some(TokenMatch {
-            len: 0,
-            new_token: Token {
-                source: "",
-                data: TokenData::Leaf(0..0),
-            },
-        })
+    fn match_at<'a>(
+        &'a self,
+        _: &[Token<'a>],
+        _: usize,
+        _: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        Some(match_nil())
     }
 }
 
@@ -413,24 +671,499 @@ pub struct ExceptSeq {
 }
 
 impl Sequence for ExceptSeq {
-    fn match_tokens<'a>(&'a self, tokens: &[Token<'a>], refs: &'a RefMap) -> Option<TokenMatch> {
-        let Some(first_token) = tokens.get(0) else {
-            return None;
-        };
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match_except(tokens, pos, ctx, |ctx| {
+            self.except.match_at(tokens, pos, ctx).is_some()
+        })
+    }
+}
 
-        if self.except.match_tokens(tokens, refs).is_some() {
-            None
-        } else {
+impl ExceptSeq {
+    pub fn new(except: Box<dyn Sequence>) -> Self {
+        Self { except }
+    }
+}
+
+pub struct RecoverSeq {
+    pub seq: Box<dyn Sequence>,
+    pub sync: Option<Box<dyn Sequence>>,
+}
+
+impl Sequence for RecoverSeq {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match_recover(
+            tokens,
+            pos,
+            ctx,
+            |ctx| self.seq.match_at(tokens, pos, ctx),
+            |ctx, at| match &self.sync {
+                Some(sync) => sync.match_at(tokens, at, ctx).map(|m| m.len),
+                None => match_line_end(tokens, at),
+            },
+        )
+    }
+}
+
+impl RecoverSeq {
+    pub fn new(seq: Box<dyn Sequence>, sync: Option<Box<dyn Sequence>>) -> Self {
+        Self { seq, sync }
+    }
+}
+
+// Matches nothing, but only if `seq` matches here, or with `negate`, only if
+// it does not.
+pub struct LookaheadSeq {
+    pub seq: Box<dyn Sequence>,
+    pub negate: bool,
+}
+
+impl Sequence for LookaheadSeq {
+    fn match_at<'a>(
+        &'a self,
+        tokens: &[Token<'a>],
+        pos: usize,
+        ctx: &mut ParseContext<'a>,
+    ) -> Option<TokenMatch<'a>> {
+        match_lookahead(tokens, pos, ctx, self.negate, |ctx| {
+            self.seq.match_at(tokens, pos, ctx).is_some()
+        })
+    }
+}
+
+impl LookaheadSeq {
+    pub fn new(seq: Box<dyn Sequence>, negate: bool) -> Self {
+        Self { seq, negate }
+    }
+}
+
+// The primitive matchers behind the sequences above, shared with parsers
+// generated by `codegen::generate_parser`.
+pub fn match_raw<'a>(
+    target: &str,
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut content = String::new();
+    let mut len = 0;
+    while let Some(t) = tokens.get(pos + len) {
+        if content.len() >= target.len() {
+            break;
+        }
+        content += t.content();
+        len += 1;
+        if !target.starts_with(&content) {
+            break;
+        }
+    }
+    if len == 0 || content != target {
+        ctx.fail(pos, Expected::Literal(target.to_string()));
+        None
+    } else if len == 1 {
+        Some(TokenMatch {
+            len,
+            new_token: tokens[pos].clone(),
+        })
+    } else {
+        let children = tokens[pos..pos + len]
+            .iter()
+            .map(|t| ("".to_string(), t.clone()))
+            .collect();
+        Some(branch_at(tokens, pos, len, children))
+    }
+}
+
+pub fn match_range<'a>(
+    start: u32,
+    end: u32,
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    match tokens.get(pos) {
+        Some(t)
+            if t.content()
+                .chars()
+                .next()
+                .is_some_and(|c| (start..=end).contains(&(c as u32))) =>
+        {
             Some(TokenMatch {
                 len: 1,
-                new_token: first_token.clone(),
+                new_token: t.clone(),
             })
         }
+        _ => {
+            ctx.fail(pos, Expected::Range(start, end));
+            None
+        }
     }
 }
 
-impl ExceptSeq {
-    pub fn new(except: Box<dyn Sequence>) -> Self {
-        Self { except }
+pub fn match_any<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    match tokens.get(pos) {
+        Some(t) => Some(TokenMatch {
+            len: 1,
+            new_token: t.clone(),
+        }),
+        None => {
+            ctx.fail(pos, Expected::Any);
+            None
+        }
     }
 }
+
+pub fn match_whitespace<'a>(tokens: &[Token<'a>], pos: usize) -> TokenMatch<'a> {
+    let mut match_index = 0;
+    let mut children = vec![];
+    while let Some(t) = tokens
+        .get(pos + match_index)
+        .filter(|t| t.content().chars().all(|c| c.is_whitespace()))
+    {
+        children.push(("".to_string(), t.clone()));
+        match_index += 1;
+    }
+    branch_at(tokens, pos, match_index, children)
+}
+
+pub fn match_nil<'a>() -> TokenMatch<'a> {
+    TokenMatch {
+        len: 0,
+        new_token: Token {
+            source: "",
+            file: FileId::default(),
+            rule: None,
+            alt: None,
+            value: None,
+            data: TokenData::Leaf(0..0),
+        },
+    }
+}
+
+// Matches any one token unless `except` matches at the same position.
+pub fn match_except<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+    except: impl FnOnce(&mut ParseContext<'a>) -> bool,
+) -> Option<TokenMatch<'a>> {
+    let Some(first_token) = tokens.get(pos) else {
+        ctx.fail(pos, Expected::Any);
+        return None;
+    };
+
+    if ctx.silently(except) {
+        ctx.fail(pos, Expected::Except);
+        None
+    } else {
+        Some(TokenMatch {
+            len: 1,
+            new_token: first_token.clone(),
+        })
+    }
+}
+
+// A match that takes no input would match forever, so it ends the repetition
+// and stands in for any matches still needed to reach `min`.
+pub fn match_repeat<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+    name: &str,
+    min: usize,
+    max: Option<usize>,
+    mut seq: impl FnMut(&mut ParseContext<'a>, usize) -> Option<TokenMatch<'a>>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    let mut stalled = false;
+    while max.is_none_or(|max| children.len() < max) && !stalled {
+        let Some(matched) = seq(ctx, pos + len) else {
+            break;
+        };
+        stalled = matched.len == 0;
+        children.push((name.to_string(), matched.new_token));
+        len += matched.len;
+    }
+    if ctx.cut_failed() || (children.len() < min && !stalled) {
+        return None;
+    }
+    Some(branch_at(tokens, pos, len, children))
+}
+
+// Separators are dropped from the tree unless they have a name. A separator
+// is only taken when an item follows it, or when `trailing` is set. As with
+// `match_repeat`, an item and separator that take no input end the list.
+#[allow(clippy::too_many_arguments)]
+pub fn match_separated<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+    item_name: &str,
+    sep_name: Option<&str>,
+    min: usize,
+    trailing: bool,
+    mut item: impl FnMut(&mut ParseContext<'a>, usize) -> Option<TokenMatch<'a>>,
+    mut sep: impl FnMut(&mut ParseContext<'a>, usize) -> Option<TokenMatch<'a>>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    let mut count = 0;
+    let mut stalled = false;
+    let mut pending: Option<TokenMatch<'a>> = None;
+    while !stalled {
+        let at = pos + len + pending.as_ref().map_or(0, |m| m.len);
+        let Some(matched) = item(ctx, at) else {
+            break;
+        };
+        if let Some(separator) = pending.take() {
+            len += separator.len;
+            if let Some(name) = sep_name {
+                children.push((name.to_string(), separator.new_token));
+            }
+        }
+        let item_len = matched.len;
+        children.push((item_name.to_string(), matched.new_token));
+        len += item_len;
+        count += 1;
+        match sep(ctx, pos + len) {
+            Some(separator) => {
+                stalled = item_len == 0 && separator.len == 0;
+                pending = Some(separator);
+            }
+            None => break,
+        }
+    }
+    if ctx.cut_failed() || (count < min && !stalled) {
+        return None;
+    }
+    if let Some(separator) = pending.filter(|_| trailing) {
+        len += separator.len;
+        if let Some(name) = sep_name {
+            children.push((name.to_string(), separator.new_token));
+        }
+    }
+    Some(branch_at(tokens, pos, len, children))
+}
+
+// A failed positive lookahead reports what `seq` expected; a negative one
+// runs silently, since what it expected is what must not be there.
+pub fn match_lookahead<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+    negate: bool,
+    seq: impl FnOnce(&mut ParseContext<'a>) -> bool,
+) -> Option<TokenMatch<'a>> {
+    let matched = match negate {
+        true => ctx.silently(seq),
+        false => ctx.peek(seq),
+    };
+    if matched == negate {
+        if negate {
+            ctx.fail(pos, Expected::Except);
+        }
+        return None;
+    }
+    Some(branch_at(tokens, pos, 0, vec![]))
+}
+
+// Matches `seq`, or when recovering, skips from `pos` through the first match
+// of `sync` (which gives its length) and leaves an error node for what was
+// skipped. Only a failure that got past `pos`, or through a cut, is a syntax
+// error: one right at `pos` is where a list of `seq`s ends, so it fails as
+// usual. At the end of the input there is nothing to skip either.
+pub fn match_recover<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+    seq: impl FnOnce(&mut ParseContext<'a>) -> Option<TokenMatch<'a>>,
+    mut sync: impl FnMut(&mut ParseContext<'a>, usize) -> Option<usize>,
+) -> Option<TokenMatch<'a>> {
+    if !ctx.recovering() || pos >= tokens.len() {
+        return seq(ctx);
+    }
+    let error = match ctx.attempt(tokens, seq) {
+        Ok(matched) => return Some(matched),
+        Err(error) => match ctx.take_cut_error() {
+            Some(error) => error,
+            None if error.index <= pos => {
+                for expected in error.expected {
+                    ctx.fail(pos, expected);
+                }
+                return None;
+            }
+            None => error,
+        },
+    };
+    let mut len = 0;
+    while pos + len < tokens.len() {
+        if let Some(synced) = ctx.silently(|ctx| sync(ctx, pos + len)) {
+            // Always skip something, or a repetition would recover forever.
+            len = (len + synced).max(1);
+            break;
+        }
+        len += 1;
+    }
+    Some(error_match(tokens, pos, len, error))
+}
+
+// The default place to resume after an error: just past the end of the line.
+pub fn match_line_end(tokens: &[Token<'_>], pos: usize) -> Option<usize> {
+    tokens
+        .get(pos)
+        .filter(|t| t.content().ends_with('\n'))
+        .map(|_| 1)
+}
+
+fn error_match<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    len: usize,
+    error: ParseError,
+) -> TokenMatch<'a> {
+    let skipped = &tokens[pos..pos + len];
+    let source = source_at(tokens, pos);
+    let start = skipped
+        .first()
+        .and_then(|t| t.content_range())
+        .map_or(source.len(), |r| r.start);
+    let end = skipped
+        .iter()
+        .rev()
+        .find(|t| std::ptr::eq(t.source, source))
+        .and_then(|t| t.content_range())
+        .map_or(start, |r| r.end);
+    TokenMatch {
+        len,
+        new_token: Token {
+            source,
+            file: file_at(tokens, pos),
+            rule: None,
+            alt: None,
+            value: None,
+            data: TokenData::Error(start..end, Box::new(error)),
+        },
+    }
+}
+
+// Records `rule` as having produced a branch. A rule that only passes on the
+// branch of a rule inside it, as in `a = b`, takes it over, so the name is
+// always the outermost rule's. Leaves are left untagged.
+pub fn rule_match<'a>(rule: &'a str, mut matched: TokenMatch<'a>) -> TokenMatch<'a> {
+    if let TokenData::Branch(_) = matched.new_token.data {
+        matched.new_token.rule = Some(rule);
+    }
+    matched
+}
+
+// The single named child a `ChooseSeq` leaves for the alternative it took.
+pub fn choose_match<'a>(alt: usize, name: &str, matched: TokenMatch<'a>) -> TokenMatch<'a> {
+    let mut matched = wrap_match(name, matched);
+    matched.new_token.alt = Some(alt);
+    matched
+}
+
+// Makes a match the only child of a new branch.
+pub fn wrap_match<'a>(name: &str, matched: TokenMatch<'a>) -> TokenMatch<'a> {
+    TokenMatch {
+        len: matched.len,
+        new_token: Token {
+            source: matched.new_token.source,
+            file: matched.new_token.file,
+            rule: None,
+            alt: None,
+            value: None,
+            data: TokenData::Branch(vec![(name.to_string(), matched.new_token)].into()),
+        },
+    }
+}
+
+pub fn branch_at<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    len: usize,
+    children: Vec<(String, Token<'a>)>,
+) -> TokenMatch<'a> {
+    TokenMatch {
+        len,
+        new_token: Token {
+            source: source_at(tokens, pos),
+            file: file_at(tokens, pos),
+            rule: None,
+            alt: None,
+            value: None,
+            data: TokenData::Branch(children.into()),
+        },
+    }
+}
+
+// Turns the result of matching a whole corpus into a token, reporting any
+// input left over.
+pub fn complete_match<'a>(
+    matched: Option<TokenMatch<'a>>,
+    tokens: &[Token<'a>],
+    ctx: &mut ParseContext<'a>,
+) -> Result<Token<'a>, ParseError> {
+    match matched {
+        _ if ctx.cut_failed() => Err(ctx.error(tokens)),
+        Some(matched) if matched.len == tokens.len() => Ok(matched.new_token),
+        Some(matched) => {
+            ctx.fail(matched.len, Expected::End);
+            Err(ctx.error(tokens))
+        }
+        None => Err(ctx.error(tokens)),
+    }
+}
+
+// Like `complete_match`, but always gives a tree: input that could not be
+// matched becomes an error node, either in place of the whole tree or after
+// what did match.
+pub fn recover_match<'a>(
+    matched: Option<TokenMatch<'a>>,
+    tokens: &[Token<'a>],
+    ctx: &mut ParseContext<'a>,
+) -> Recovered<'a> {
+    let matched = matched.filter(|_| !ctx.cut_failed());
+    let token = match matched {
+        Some(matched) if matched.len == tokens.len() => matched.new_token,
+        Some(matched) => {
+            ctx.fail(matched.len, Expected::End);
+            let rest = error_match(
+                tokens,
+                matched.len,
+                tokens.len() - matched.len,
+                ctx.error(tokens),
+            );
+            let children = vec![
+                ("".to_string(), matched.new_token),
+                ("".to_string(), rest.new_token),
+            ];
+            branch_at(tokens, 0, tokens.len(), children).new_token
+        }
+        None => error_match(tokens, 0, tokens.len(), ctx.error(tokens)).new_token,
+    };
+    let errors = token.errors().into_iter().cloned().collect();
+    Recovered { token, errors }
+}
+
+fn source_at<'a>(tokens: &[Token<'a>], pos: usize) -> &'a str {
+    tokens.get(pos).map_or_else(|| "", |t| t.source)
+}
+
+fn file_at(tokens: &[Token<'_>], pos: usize) -> FileId {
+    tokens.get(pos).map_or_else(FileId::default, |t| t.file)
+}
@@ -1,5 +1,19 @@
 use super::*;
 
+// Matches `rule` from `refs` at the start of `text` and describes the match
+// with `f`, or gives the error as text.
+fn match_rule<T>(
+    refs: &RefMap,
+    rule: &str,
+    text: &str,
+    f: impl FnOnce(TokenMatch<'_>) -> T,
+) -> Result<T, String> {
+    let rule = RefSeq::new(rule.to_string());
+    rule.parse_tokens(&Corpus::make(text).tokens, refs)
+        .map(f)
+        .map_err(|e| e.to_string())
+}
+
 #[test]
 pub fn mult_test() {
     let seq = MultSeq::new(vec![
@@ -191,3 +205,358 @@ pub fn except_test() {
     );
     seq.assert_matches(&Corpus::make(""), new_ref_map, TokenMatchTestType::None);
 }
+
+#[test]
+pub fn furthest_failure_test() {
+    let mut refs = RefMap::new();
+    refs.insert(
+        "digit".to_string(),
+        Box::new(RangeSeq::new('0' as u32, '9' as u32)),
+    );
+    let seq = MultSeq::new(vec![
+        (Box::new(RefSeq::new("digit".to_string())), "".to_string()),
+        (
+            Box::new(ChooseSeq::new(vec![
+                (Box::new(RawSeq::new("+".to_string())), "".to_string()),
+                (Box::new(RawSeq::new("-".to_string())), "".to_string()),
+            ])),
+            "".to_string(),
+        ),
+        (Box::new(RefSeq::new("digit".to_string())), "".to_string()),
+    ]);
+    let tokens = Corpus::make("1+x").tokens;
+    let err = seq.parse_tokens(&tokens, &refs).unwrap_err();
+    assert_eq!(err.index, 2);
+    assert_eq!(err.expected, vec![Expected::Rule("digit".to_string())]);
+
+    let err = seq
+        .parse_tokens(&Corpus::make("1*2").tokens, &refs)
+        .unwrap_err();
+    assert_eq!(err.index, 1);
+    assert_eq!(
+        err.expected,
+        vec![
+            Expected::Literal("+".to_string()),
+            Expected::Literal("-".to_string())
+        ]
+    );
+    assert_eq!(err.to_string(), "expected one of `+`, `-` at 1:2");
+}
+
+#[test]
+pub fn unicode_corpus_test() {
+    let corpus = Corpus::make("日本👍");
+    assert_eq!(
+        corpus
+            .tokens
+            .iter()
+            .map(|t| t.content())
+            .collect::<Vec<_>>(),
+        vec!["日", "本", "👍"]
+    );
+    let corpus = Corpus::make_graphemes("e\u{301}👍🏽\r\n");
+    assert_eq!(
+        corpus
+            .tokens
+            .iter()
+            .map(|t| t.content())
+            .collect::<Vec<_>>(),
+        vec!["e\u{301}", "👍🏽", "\r\n"]
+    );
+    let seq = RangeSeq::new('α' as u32, 'ω' as u32);
+    seq.assert_matches(&Corpus::make("λ"), &RefMap::new(), TokenMatchTestType::All);
+    let seq = RawSeq::new("e\u{301}".to_string());
+    seq.assert_matches(
+        &Corpus::make("e\u{301}"),
+        &RefMap::new(),
+        TokenMatchTestType::All,
+    );
+}
+
+fn nested_refs() -> RefMap {
+    crate::meta::eval_rule_set(
+        "
+    expr = (term & '+' & expr) | (term & '-' & expr) | term
+    term = ('(' & expr & ')') | 0..9
+    ",
+    )
+}
+
+#[test]
+pub fn memo_test() {
+    let refs = nested_refs();
+    let text = "(".repeat(8) + "1" + &")".repeat(8);
+    let corpus = Corpus::make(&text);
+    let seq = RefSeq::new("expr".to_string());
+
+    let plain = seq.parse_corpus(&corpus, &refs).unwrap();
+    let mut ctx = ParseContext::new(&refs).with_memo(MemoOptions::new());
+    let memoized = seq.parse_corpus_with(&corpus, &mut ctx).unwrap();
+    assert_eq!(plain.graph(), memoized.graph());
+    let memo = ctx.memo().unwrap();
+    assert!(memo.misses <= 2 * (text.len() + 1));
+    assert!(memo.hits > 0);
+
+    let mut ctx = ParseContext::new(&refs).with_memo(MemoOptions::only(["term"]).capacity(4));
+    seq.parse_corpus_with(&corpus, &mut ctx).unwrap();
+    assert_eq!(ctx.memo().unwrap().len(), 4);
+}
+
+#[test]
+pub fn recover_test() {
+    let refs = crate::meta::eval_rule_set("stmts = ((a..z & '=' & 0..9 & ';')@';').stmt*");
+    let corpus = Corpus::make("a=1;b=;c=3;d=x;");
+    let stmts = RefSeq::new("stmts".to_string());
+    assert!(stmts.parse_corpus(&corpus, &refs).is_err());
+
+    let recovered = stmts.parse_recovering(&corpus, &refs);
+    let skipped: Vec<_> = recovered
+        .token
+        .descendants()
+        .filter(|t| matches!(t.data, TokenData::Error(..)))
+        .map(|t| t.content())
+        .collect();
+    assert_eq!(skipped, ["b=;", "d=x;"]);
+    assert_eq!(recovered.token.content(), "a=1;b=;c=3;d=x;");
+    let errors: Vec<_> = recovered.errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        errors,
+        [
+            "expected `0`..`9` at 1:7 while parsing stmts",
+            "expected `0`..`9` at 1:14 while parsing stmts"
+        ]
+    );
+    assert_eq!(recovered.token.children().len(), 4);
+}
+
+#[test]
+pub fn recover_line_test() {
+    let seq = NoneOrMoreSeq::new(
+        Box::new(RecoverSeq::new(
+            Box::new(MultSeq::new(vec![
+                (
+                    Box::new(RangeSeq::new('0' as u32, '9' as u32)),
+                    "".to_string(),
+                ),
+                (Box::new(RawSeq::new("\n".to_string())), "".to_string()),
+            ])),
+            None,
+        )),
+        "line".to_string(),
+    );
+    let refs = RefMap::new();
+    let recovered = seq.parse_recovering(&Corpus::make("1\n2y\n3\n"), &refs);
+    assert_eq!(recovered.errors.len(), 1);
+    assert_eq!(recovered.errors[0].span, 3..4);
+    let error = recovered.token.child_at(1).unwrap();
+    assert!(matches!(error.data, TokenData::Error(..)));
+    assert_eq!(error.content(), "2y\n");
+    assert_eq!(recovered.token.child_at(2).unwrap().content(), "3\n");
+}
+
+#[test]
+pub fn recover_rest_test() {
+    let refs = crate::meta::eval_rule_set("main = 'a'+");
+    let main = RefSeq::new("main".to_string());
+
+    let recovered = main.parse_recovering(&Corpus::make("aab"), &refs);
+    assert_eq!(recovered.token.content(), "aab");
+    assert_eq!(recovered.token.child_at(1).unwrap().content(), "b");
+    assert_eq!(recovered.errors.len(), 1);
+    assert_eq!(
+        recovered.errors[0].expected,
+        [Expected::Literal("a".to_string()), Expected::End]
+    );
+
+    let recovered = main.parse_recovering(&Corpus::make("b"), &refs);
+    assert!(matches!(recovered.token.data, TokenData::Error(..)));
+    assert_eq!(recovered.token.graph(), "\nERROR \"b\"");
+    assert_eq!(recovered.errors[0].to_string(), "expected main at 1:1");
+}
+
+fn cut_refs() -> RefMap {
+    crate::meta::eval_rule_set(
+        "
+    stmt = ({if} ~ [ ] & x..z:cond & ';').if | (a..z+:name & '=' & a..z:value & ';').assign
+    stmts = stmt*
+    ",
+    )
+}
+
+#[test]
+pub fn cut_test() {
+    let refs = cut_refs();
+    let stmt = RefSeq::new("stmt".to_string());
+    let parse = |text| stmt.parse_corpus(&Corpus::make(text), &refs);
+    assert!(parse("if x;").unwrap().child("if").is_some());
+    assert!(parse("i=x;").unwrap().child("assign").is_some());
+    // Without the cut, `ifa=b;` would parse as an assignment.
+    assert_eq!(
+        parse("ifa=b;").unwrap_err().to_string(),
+        "expected ` ` at 1:3 while parsing stmt"
+    );
+    assert_eq!(
+        parse("if 1;").unwrap_err().to_string(),
+        "expected `x`..`z` at 1:4 while parsing stmt"
+    );
+
+    // A repetition stops at a failed cut rather than ending early.
+    let stmts = RefSeq::new("stmts".to_string());
+    let err = stmts
+        .parse_tokens(&Corpus::make("a=b;if 1;").tokens, &refs)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "expected `x`..`z` at 1:8 while parsing stmts > stmt"
+    );
+}
+
+#[test]
+pub fn cut_lookahead_test() {
+    // A cut inside `!` only decides whether the inner sequence matches.
+    let refs = crate::meta::eval_rule_set("main = ('a' ~ 'b')! & 'c'");
+    let main = RefSeq::new("main".to_string());
+    assert!(main.parse_corpus(&Corpus::make("ac"), &refs).is_ok());
+    assert!(main.parse_corpus(&Corpus::make("ab"), &refs).is_err());
+}
+
+#[test]
+pub fn cut_recover_test() {
+    let mut refs = cut_refs();
+    refs.extend(crate::meta::eval_rule_set("main = (stmt@';').stmt*"));
+    let main = RefSeq::new("main".to_string());
+    let recovered = main.parse_recovering(&Corpus::make("if 1;a=b;ifc=d;"), &refs);
+    let errors: Vec<_> = recovered.errors.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        errors,
+        [
+            "expected `x`..`z` at 1:4 while parsing main > stmt",
+            "expected ` ` at 1:12 while parsing main > stmt"
+        ]
+    );
+    assert_eq!(recovered.token.content(), "if 1;a=b;ifc=d;");
+}
+
+#[test]
+pub fn empty_repeat_test() {
+    // A repeated lookahead matches once without moving, then stops.
+    let refs = crate::meta::eval_rule_set(
+        "
+    none = (&'a')* & 'a'
+    one = (&'a')+ & 'a'
+    ",
+    );
+    let parse = |rule, text| {
+        match_rule(&refs, rule, text, |m| {
+            (m.len, m.new_token.child_at(0).unwrap().entries().len())
+        })
+    };
+    assert_eq!(parse("none", "a"), Ok((1, 1)));
+    assert_eq!(parse("one", "a"), Ok((1, 1)));
+    assert_eq!(parse("none", "b"), Err("expected none at 1:1".to_string()));
+    assert_eq!(parse("one", "b"), Err("expected one at 1:1".to_string()));
+}
+
+#[test]
+pub fn lookahead_test() {
+    let refs = crate::meta::eval_rule_set(
+        "
+    letter = a..z
+    keyword = {if} & !letter
+    starts = &'a' & letter+
+    after = 'x' & &'a' & letter
+    line = letter+ & !any
+    ",
+    );
+    let parse = |rule, text| {
+        match_rule(&refs, rule, text, |m| {
+            let second = m.new_token.child_at(1).map(|t| t.content().to_string());
+            (m.len, second)
+        })
+    };
+    assert_eq!(parse("keyword", "if("), Ok((2, Some("".to_string()))));
+    assert_eq!(
+        parse("keyword", "iffy"),
+        Err("expected a different token at 1:3 while parsing keyword".to_string())
+    );
+    assert_eq!(parse("starts", "abc").map(|(len, _)| len), Ok(3));
+    // At the start of a rule, the failure collapses into the rule's name as
+    // usual; elsewhere it is what the inner sequence expected.
+    assert_eq!(
+        parse("starts", "bcd"),
+        Err("expected starts at 1:1".to_string())
+    );
+    assert_eq!(
+        parse("after", "xb"),
+        Err("expected `a` at 1:2 while parsing after".to_string())
+    );
+    assert_eq!(parse("line", "ab").map(|(len, _)| len), Ok(2));
+    assert!(parse("line", "ab1").is_err());
+}
+
+#[test]
+pub fn repeat_test() {
+    let refs = crate::meta::eval_rule_set(
+        "
+    hex = [0123456789abcdef]
+    word = hex.digit{4}
+    short = hex.digit{1,3}
+    many = hex.digit{2,}
+    blank = _.gap{3}
+    ",
+    );
+    let count = |name| move |m: TokenMatch<'_>| (m.len, m.new_token.children_named(name).count());
+    let parse = |rule, text| match_rule(&refs, rule, text, count("digit")).ok();
+    assert_eq!(parse("word", "beef0"), Some((4, 4)));
+    assert_eq!(parse("word", "bee"), None);
+    assert_eq!(parse("short", "abcdef"), Some((3, 3)));
+    assert_eq!(parse("short", "xyz"), None);
+    assert_eq!(parse("many", "a"), None);
+    assert_eq!(parse("many", "abcdef"), Some((6, 6)));
+    // A match that takes no input is not repeated.
+    assert_eq!(match_rule(&refs, "blank", "x", count("gap")), Ok((0, 1)));
+}
+
+#[test]
+pub fn sep_test() {
+    let refs = crate::meta::eval_rule_set(
+        "
+    num = 0..9+
+    list = num.item % ','
+    kept = num.item %1 (_ + ',' + _).comma
+    trailing = num.item %%2 ';'
+    ",
+    );
+    let parse = |rule, text| {
+        match_rule(&refs, rule, text, |m| {
+            let names: Vec<String> = m
+                .new_token
+                .entries()
+                .iter()
+                .map(|(name, child)| format!("{name}={}", child.content()))
+                .collect();
+            (m.len, names)
+        })
+        .ok()
+    };
+    assert_eq!(
+        parse("list", "1,22,3"),
+        Some((6, vec!["item=1".into(), "item=22".into(), "item=3".into()]))
+    );
+    // A separator with no item after it is left unmatched.
+    assert_eq!(parse("list", "1,"), Some((1, vec!["item=1".into()])));
+    assert_eq!(parse("list", ""), Some((0, vec![])));
+    assert_eq!(
+        parse("kept", "1 , 2"),
+        Some((
+            5,
+            vec!["item=1".into(), "comma= , ".into(), "item=2".into()]
+        ))
+    );
+    assert_eq!(parse("kept", ","), None);
+    assert_eq!(
+        parse("trailing", "1;2;"),
+        Some((4, vec!["item=1".into(), "item=2".into()]))
+    );
+    assert_eq!(parse("trailing", "1;"), None);
+}
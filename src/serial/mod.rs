@@ -0,0 +1,401 @@
+use core::ops::Range;
+use std::error::Error;
+use std::fmt::Display;
+
+use crate::report::json_string;
+use crate::token::*;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenTree {
+    pub span: Option<Range<usize>>,
+    pub data: TreeData,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeData {
+    Leaf(String),
+    Branch(Vec<(String, TokenTree)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeParseError {
+    pub message: String,
+    pub offset: usize,
+}
+
+impl Display for TreeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl Error for TreeParseError {}
+
+impl From<&Token<'_>> for TokenTree {
+    fn from(token: &Token<'_>) -> Self {
+        TokenTree {
+            span: token.content_range(),
+            data: match &token.data {
+                // Error nodes are saved as the text they skipped.
+                TokenData::Leaf(_) | TokenData::Error(..) => {
+                    TreeData::Leaf(token.content().to_string())
+                }
+                TokenData::Branch(children) => TreeData::Branch(
+                    children
+                        .iter()
+                        .map(|(name, child)| (name.clone(), TokenTree::from(child)))
+                        .collect(),
+                ),
+            },
+        }
+    }
+}
+
+impl Token<'_> {
+    pub fn to_json(&self) -> String {
+        TokenTree::from(self).to_json()
+    }
+
+    pub fn to_sexpr(&self) -> String {
+        TokenTree::from(self).to_sexpr()
+    }
+
+    pub fn to_text(&self) -> String {
+        TokenTree::from(self).to_text()
+    }
+}
+
+impl TokenTree {
+    pub fn to_json(&self) -> String {
+        self.json_named("")
+    }
+
+    fn json_named(&self, name: &str) -> String {
+        let span = self
+            .span
+            .as_ref()
+            .map_or("null".to_string(), |r| format!("[{},{}]", r.start, r.end));
+        let body = match &self.data {
+            TreeData::Leaf(text) => format!("\"text\":{}", json_string(text)),
+            TreeData::Branch(children) => {
+                let children: Vec<String> = children
+                    .iter()
+                    .map(|(name, child)| child.json_named(name))
+                    .collect();
+                format!("\"children\":[{}]", children.join(","))
+            }
+        };
+        format!("{{\"name\":{},\"span\":{span},{body}}}", json_string(name))
+    }
+
+    // Leaves are quoted strings and branches are parenthesised lists; either
+    // may be followed by `@start..end`, and a child may be prefixed by
+    // `name=`, for example `(@0..2 lhs="1"@0..1 "+"@1..2)`.
+    pub fn to_sexpr(&self) -> String {
+        let span = self
+            .span
+            .as_ref()
+            .map_or(String::new(), |r| format!("@{}..{}", r.start, r.end));
+        match &self.data {
+            TreeData::Leaf(text) => format!("{}{span}", json_string(text)),
+            TreeData::Branch(children) => {
+                let mut out = "(".to_string() + &span;
+                for (name, child) in children {
+                    if out.len() > 1 {
+                        out += " ";
+                    }
+                    if !name.is_empty() {
+                        out += &label(name);
+                        out += "=";
+                    }
+                    out += &child.to_sexpr();
+                }
+                out + ")"
+            }
+        }
+    }
+
+    // One line per token: two spaces of indent per level, the quoted child
+    // name, `leaf` or `branch`, the span as `start..end` or `-`, and for
+    // leaves the quoted text, for example `  "lhs" leaf 0..1 "1"`.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        self.text_lines("", 0, &mut out);
+        out
+    }
+
+    fn text_lines(&self, name: &str, depth: usize, out: &mut String) {
+        let span = self
+            .span
+            .as_ref()
+            .map_or("-".to_string(), |r| format!("{}..{}", r.start, r.end));
+        *out += &"  ".repeat(depth);
+        match &self.data {
+            TreeData::Leaf(text) => {
+                *out += &format!("{} leaf {span} {}\n", json_string(name), json_string(text))
+            }
+            TreeData::Branch(children) => {
+                *out += &format!("{} branch {span}\n", json_string(name));
+                for (name, child) in children {
+                    child.text_lines(name, depth + 1, out);
+                }
+            }
+        }
+    }
+
+    pub fn from_sexpr(text: &str) -> Result<Self, TreeParseError> {
+        let mut reader = Reader {
+            text,
+            pos: 0,
+            base: 0,
+        };
+        let tree = reader.sexpr_node()?;
+        reader.skip_ws();
+        if reader.pos < text.len() {
+            return Err(reader.error("expected end of input"));
+        }
+        Ok(tree)
+    }
+
+    pub fn from_text(text: &str) -> Result<Self, TreeParseError> {
+        let mut lines = vec![];
+        let mut offset = 0;
+        for line in text.split_inclusive('\n') {
+            let content = line.trim_end_matches(['\n', '\r']);
+            if !content.trim().is_empty() {
+                let depth = content.len() - content.trim_start_matches(' ').len();
+                if depth % 2 != 0 {
+                    return Err(TreeParseError {
+                        message: "indent must be a multiple of two spaces".to_string(),
+                        offset,
+                    });
+                }
+                lines.push((depth / 2, offset + depth, &content[depth..]));
+            }
+            offset += line.len();
+        }
+        let mut index = 0;
+        let (name, tree) = text_node(&lines, &mut index, 0)?;
+        if let Some((_, offset, _)) = lines.get(index) {
+            return Err(TreeParseError {
+                message: "expected a single root token".to_string(),
+                offset: *offset,
+            });
+        }
+        if !name.is_empty() {
+            return Err(TreeParseError {
+                message: "the root token must not be named".to_string(),
+                offset: 0,
+            });
+        }
+        Ok(tree)
+    }
+}
+
+fn label(name: &str) -> String {
+    if name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        name.to_string()
+    } else {
+        json_string(name)
+    }
+}
+
+fn text_node(
+    lines: &[(usize, usize, &str)],
+    index: &mut usize,
+    depth: usize,
+) -> Result<(String, TokenTree), TreeParseError> {
+    let Some((line_depth, offset, line)) = lines.get(*index) else {
+        return Err(TreeParseError {
+            message: "expected a token".to_string(),
+            offset: 0,
+        });
+    };
+    let mut reader = Reader {
+        text: line,
+        pos: 0,
+        base: *offset,
+    };
+    if *line_depth != depth {
+        return Err(reader.error("unexpected indent"));
+    }
+    *index += 1;
+    let name = reader.string()?;
+    reader.skip_ws();
+    let kind = reader.word();
+    reader.skip_ws();
+    let span = if reader.eat("-") {
+        None
+    } else {
+        Some(reader.range()?)
+    };
+    let data = match kind {
+        "leaf" => {
+            reader.skip_ws();
+            TreeData::Leaf(reader.string()?)
+        }
+        "branch" => {
+            let mut children = vec![];
+            while lines.get(*index).is_some_and(|(d, ..)| *d > depth) {
+                children.push(text_node(lines, index, depth + 1)?);
+            }
+            TreeData::Branch(children)
+        }
+        _ => return Err(reader.error("expected `leaf` or `branch`")),
+    };
+    reader.skip_ws();
+    if reader.pos < line.len() {
+        return Err(reader.error("expected end of line"));
+    }
+    Ok((name, TokenTree { span, data }))
+}
+
+pub(crate) struct Reader<'a> {
+    pub text: &'a str,
+    pub pos: usize,
+    pub base: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn error(&self, message: &str) -> TreeParseError {
+        TreeParseError {
+            message: message.to_string(),
+            offset: self.base + self.pos,
+        }
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    pub fn skip_ws(&mut self) {
+        self.pos = self.text.len() - self.rest().trim_start().len();
+    }
+
+    pub fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn word(&mut self) -> &'a str {
+        let start = self.pos;
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(self.rest().len());
+        self.pos += len;
+        &self.text[start..self.pos]
+    }
+
+    pub fn number(&mut self) -> Result<usize, TreeParseError> {
+        let len = self
+            .rest()
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest().len());
+        let number = self.rest()[..len]
+            .parse()
+            .map_err(|_| self.error("expected a number"))?;
+        self.pos += len;
+        Ok(number)
+    }
+
+    fn range(&mut self) -> Result<Range<usize>, TreeParseError> {
+        let start = self.number()?;
+        if !self.eat("..") {
+            return Err(self.error("expected `..`"));
+        }
+        Ok(start..self.number()?)
+    }
+
+    pub fn string(&mut self) -> Result<String, TreeParseError> {
+        if !self.eat("\"") {
+            return Err(self.error("expected a string"));
+        }
+        let mut out = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(out);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => out.push('\n'),
+                    Some((_, 'r')) => out.push('\r'),
+                    Some((_, 't')) => out.push('\t'),
+                    Some((_, 'u')) => {
+                        let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| self.error("invalid `\\u` escape"))?;
+                        out.push(c);
+                    }
+                    Some((_, c)) => out.push(c),
+                    None => break,
+                },
+                c => out.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn sexpr_span(&mut self) -> Result<Option<Range<usize>>, TreeParseError> {
+        if self.eat("@") {
+            self.range().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn sexpr_node(&mut self) -> Result<TokenTree, TreeParseError> {
+        self.skip_ws();
+        if self.rest().starts_with('"') {
+            let text = self.string()?;
+            let span = self.sexpr_span()?;
+            return Ok(TokenTree {
+                span,
+                data: TreeData::Leaf(text),
+            });
+        }
+        if !self.eat("(") {
+            return Err(self.error("expected `(` or a string"));
+        }
+        let span = self.sexpr_span()?;
+        let mut children = vec![];
+        loop {
+            self.skip_ws();
+            if self.eat(")") {
+                break;
+            }
+            let start = self.pos;
+            let name = if self.rest().starts_with('"') {
+                Some(self.string()?)
+            } else {
+                Some(self.word().to_string()).filter(|w| !w.is_empty())
+            };
+            let name = match name {
+                Some(name) if self.eat("=") => name,
+                _ => {
+                    self.pos = start;
+                    String::new()
+                }
+            };
+            children.push((name, self.sexpr_node()?));
+        }
+        Ok(TokenTree {
+            span,
+            data: TreeData::Branch(children),
+        })
+    }
+}
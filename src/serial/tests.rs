@@ -0,0 +1,84 @@
+use super::*;
+use crate::corpus::*;
+use crate::meta::*;
+
+fn sample() -> TokenTree {
+    let refs = eval_rule_set("main = 0..9:lhs & [+-]:oper & 'x'?:opt & 'y'?:none");
+    let corpus = Corpus::make("1+x");
+    let token = refs["main"]
+        .match_corpus_first(&corpus, &refs)
+        .unwrap()
+        .new_token;
+    TokenTree::from(&token)
+}
+
+#[test]
+pub fn json_test() {
+    let refs = eval_rule_set("main = 0..9:lhs & '+'");
+    let corpus = Corpus::make("1+");
+    let token = refs["main"]
+        .match_corpus_first(&corpus, &refs)
+        .unwrap()
+        .new_token;
+    assert_eq!(
+        token.to_json(),
+        "{\"name\":\"\",\"span\":[0,2],\"children\":[\
+{\"name\":\"lhs\",\"span\":[0,1],\"text\":\"1\"},\
+{\"name\":\"\",\"span\":[1,2],\"text\":\"+\"}]}"
+    );
+    assert_eq!(token.to_sexpr(), "(@0..2 lhs=\"1\"@0..1 \"+\"@1..2)");
+    assert_eq!(
+        token.to_text(),
+        "\"\" branch 0..2\n  \"lhs\" leaf 0..1 \"1\"\n  \"\" leaf 1..2 \"+\"\n"
+    );
+}
+
+#[test]
+pub fn round_trip_test() {
+    let tree = sample();
+    let sexpr = tree.to_sexpr();
+    assert_eq!(TokenTree::from_sexpr(&sexpr), Ok(tree.clone()));
+    assert_eq!(TokenTree::from_text(&tree.to_text()), Ok(tree.clone()));
+
+    let odd = TokenTree {
+        span: None,
+        data: TreeData::Branch(vec![
+            (
+                "rhs's".to_string(),
+                TokenTree {
+                    span: Some(3..4),
+                    data: TreeData::Leaf("\"\n\u{1}λ".to_string()),
+                },
+            ),
+            (
+                "".to_string(),
+                TokenTree {
+                    span: None,
+                    data: TreeData::Branch(vec![]),
+                },
+            ),
+        ]),
+    };
+    assert_eq!(TokenTree::from_sexpr(&odd.to_sexpr()), Ok(odd.clone()));
+    assert_eq!(TokenTree::from_text(&odd.to_text()), Ok(odd));
+}
+
+#[test]
+pub fn parse_error_test() {
+    assert_eq!(
+        TokenTree::from_sexpr("(\"a\"@0..1").unwrap_err().message,
+        "expected `(` or a string"
+    );
+    assert_eq!(
+        TokenTree::from_text("\"\" branch -\n   \"a\" leaf - \"a\"")
+            .unwrap_err()
+            .offset,
+        12
+    );
+    assert_eq!(
+        TokenTree::from_text("\"\" leaf 0..1 \"a\" extra")
+            .unwrap_err()
+            .message,
+        "expected end of line"
+    );
+}
@@ -0,0 +1,193 @@
+use core::ops::Range;
+
+use crate::corpus::*;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnUnit {
+    Utf8,
+    Utf16,
+    Char,
+}
+
+// Tokens made without a file, such as by `Corpus::make`, all share the
+// default ID; `SourceDb` hands out IDs starting at 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(pub usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span<'a> {
+    pub file: FileId,
+    pub source: &'a str,
+    pub range: Range<usize>,
+}
+
+impl<'a> Span<'a> {
+    pub fn text(&self) -> &'a str {
+        &self.source[self.range.clone()]
+    }
+}
+
+// Lines and columns both start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SourceMap<'a> {
+    pub name: Option<String>,
+    pub source: &'a str,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> SourceMap<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            name: None,
+            source,
+            line_starts,
+        }
+    }
+
+    pub fn named(name: impl Into<String>, source: &'a str) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..Self::new(source)
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.source.len(), |next| next - 1);
+        Some(start..end)
+    }
+
+    pub fn line_text(&self, line: usize) -> Option<&'a str> {
+        self.line_range(line)
+            .map(|range| self.source[range].trim_end_matches('\r'))
+    }
+
+    // Offsets past the end are clamped, and offsets inside a character are
+    // moved back to its start.
+    pub fn position(&self, byte: usize, unit: ColumnUnit) -> Position {
+        let mut byte = byte.min(self.source.len());
+        while !self.source.is_char_boundary(byte) {
+            byte -= 1;
+        }
+        let line = self.line_starts.partition_point(|start| *start <= byte);
+        let before = &self.source[self.line_starts[line - 1]..byte];
+        Position {
+            line,
+            column: column_width(before, unit) + 1,
+        }
+    }
+
+    pub fn offset(&self, position: Position, unit: ColumnUnit) -> Option<usize> {
+        let range = self.line_range(position.line)?;
+        let mut width = 0;
+        for (i, c) in self.source[range.clone()].char_indices() {
+            if width + 1 == position.column {
+                return Some(range.start + i);
+            }
+            if width + 1 > position.column {
+                return None;
+            }
+            width += char_width(c, unit);
+        }
+        (width + 1 == position.column).then_some(range.end)
+    }
+
+    pub fn span_positions(&self, span: Range<usize>, unit: ColumnUnit) -> Range<Position> {
+        self.position(span.start, unit)..self.position(span.end, unit)
+    }
+}
+
+fn char_width(c: char, unit: ColumnUnit) -> usize {
+    match unit {
+        ColumnUnit::Utf8 => c.len_utf8(),
+        ColumnUnit::Utf16 => c.len_utf16(),
+        ColumnUnit::Char => 1,
+    }
+}
+
+fn column_width(text: &str, unit: ColumnUnit) -> usize {
+    match unit {
+        ColumnUnit::Utf8 => text.len(),
+        _ => text.chars().map(|c| char_width(c, unit)).sum(),
+    }
+}
+
+pub struct SourceFile {
+    pub id: FileId,
+    pub name: String,
+    pub text: String,
+}
+
+#[derive(Default)]
+pub struct SourceDb {
+    files: Vec<SourceFile>,
+}
+
+impl SourceDb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, name: impl Into<String>, text: impl Into<String>) -> FileId {
+        let id = FileId(self.files.len() + 1);
+        self.files.push(SourceFile {
+            id,
+            name: name.into(),
+            text: text.into(),
+        });
+        id
+    }
+
+    pub fn get(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get(id.0.checked_sub(1)?)
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &SourceFile> {
+        self.files.iter()
+    }
+
+    pub fn name(&self, id: FileId) -> Option<&str> {
+        self.get(id).map(|f| f.name.as_str())
+    }
+
+    pub fn text(&self, id: FileId) -> Option<&str> {
+        self.get(id).map(|f| f.text.as_str())
+    }
+
+    pub fn corpus(&self, id: FileId) -> Option<Corpus<'_>> {
+        self.get(id).map(|f| Corpus::make_in(&f.text, id))
+    }
+
+    pub fn source_map(&self, id: FileId) -> Option<SourceMap<'_>> {
+        self.get(id)
+            .map(|f| SourceMap::named(f.name.clone(), &f.text))
+    }
+
+    pub fn span_text(&self, span: &Span<'_>) -> Option<&str> {
+        self.text(span.file)?.get(span.range.clone())
+    }
+}
+
+pub fn line_col(text: &str, byte: usize) -> (usize, usize) {
+    let position = SourceMap::new(text).position(byte, ColumnUnit::Char);
+    (position.line, position.column)
+}
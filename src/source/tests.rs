@@ -0,0 +1,83 @@
+use super::*;
+use crate::meta::*;
+use crate::token::*;
+
+#[test]
+pub fn position_test() {
+    let map = SourceMap::named("poem.txt", "ab\r\nλ😀c\n\nz");
+    assert_eq!(map.line_count(), 4);
+    assert_eq!(map.line_text(2), Some("λ😀c"));
+    assert_eq!(map.line_text(3), Some(""));
+    assert_eq!(map.line_text(5), None);
+
+    let c = "ab\r\nλ😀".len();
+    let at = |line, column| Position { line, column };
+    assert_eq!(map.position(c, ColumnUnit::Utf8), at(2, 7));
+    assert_eq!(map.position(c, ColumnUnit::Utf16), at(2, 4));
+    assert_eq!(map.position(c, ColumnUnit::Char), at(2, 3));
+    assert_eq!(map.position(c - 1, ColumnUnit::Char), at(2, 2));
+    assert_eq!(map.position(100, ColumnUnit::Char), at(4, 2));
+
+    for unit in [ColumnUnit::Utf8, ColumnUnit::Utf16, ColumnUnit::Char] {
+        for byte in (0..=map.source.len()).filter(|b| map.source.is_char_boundary(*b)) {
+            assert_eq!(map.offset(map.position(byte, unit), unit), Some(byte));
+        }
+    }
+    assert_eq!(map.offset(at(2, 3), ColumnUnit::Utf16), None);
+    assert_eq!(map.offset(at(1, 9), ColumnUnit::Char), None);
+}
+
+#[test]
+pub fn token_position_test() {
+    let refs = eval_rule_set("main = 'a'+");
+    let map = SourceMap::new("one\ntwo aa");
+    let corpus = Corpus::make(map.source);
+    let token = refs["main"]
+        .match_tokens(&corpus.tokens[8..], &refs)
+        .unwrap();
+    assert_eq!(
+        token.new_token.start_position(&map, ColumnUnit::Char),
+        Some(Position { line: 2, column: 5 })
+    );
+    assert_eq!(
+        token.new_token.end_position(&map, ColumnUnit::Char),
+        Some(Position { line: 2, column: 7 })
+    );
+}
+
+#[test]
+pub fn source_db_test() {
+    let mut db = SourceDb::new();
+    let main = db.add("main.txt", "ab[");
+    let include = db.add("include.txt", "cd");
+    assert_eq!(db.name(include), Some("include.txt"));
+    assert_eq!(db.get(FileId::default()).map(|f| f.id), None);
+
+    let main_corpus = db.corpus(main).unwrap();
+    let mut tokens = main_corpus.tokens[..2].to_vec();
+    tokens.extend(db.corpus(include).unwrap().tokens);
+    tokens.push(main_corpus.tokens[2].clone());
+    let refs = eval_rule_set("main = a..z+:letters & '['");
+    let matched = refs["main"]
+        .match_corpus_first(&Corpus::new(tokens), &refs)
+        .unwrap()
+        .new_token;
+
+    assert_eq!(matched.content_range(), None);
+    assert_eq!(matched.content(), "");
+    let spans = matched.spans();
+    assert_eq!(
+        spans
+            .iter()
+            .map(|s| (s.file, s.range.clone(), s.text()))
+            .collect::<Vec<_>>(),
+        vec![(main, 0..2, "ab"), (include, 0..2, "cd"), (main, 2..3, "[")]
+    );
+    assert_eq!(db.span_text(&spans[1]), Some("cd"));
+
+    let letters = matched.get_first_child("letters").unwrap();
+    assert_eq!(letters.spans().len(), 2);
+    let bracket = matched.get_children("").pop().unwrap();
+    assert_eq!(bracket.file, main);
+    assert_eq!(bracket.content(), "[");
+}
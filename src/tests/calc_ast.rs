@@ -0,0 +1,162 @@
+// Generated by tuckey from a grammar; do not edit.
+
+pub type Nonzero = ::std::string::String;
+
+pub type Digit = ::std::string::String;
+
+pub type PosInt = ::std::string::String;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Oper {
+    Add,
+    Sub,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for Oper {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        match &token.data {
+            ::tuckey::token::TokenData::Branch(children) if children.len() == 1 => {
+                let (name, _) = &children[0];
+                match name.as_str() {
+                    "add" => Ok(Oper::Add),
+                    "sub" => Ok(Oper::Sub),
+                    _ => Err(::tuckey::ast::AstError::at("expected one of `add`, `sub`", token)),
+                }
+            }
+            _ => Err(::tuckey::ast::AstError::at("expected one of `add`, `sub`", token)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MultOper {
+    Mul,
+    Div,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for MultOper {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        match &token.data {
+            ::tuckey::token::TokenData::Branch(children) if children.len() == 1 => {
+                let (name, _) = &children[0];
+                match name.as_str() {
+                    "mul" => Ok(MultOper::Mul),
+                    "div" => Ok(MultOper::Div),
+                    _ => Err(::tuckey::ast::AstError::at("expected one of `mul`, `div`", token)),
+                }
+            }
+            _ => Err(::tuckey::ast::AstError::at("expected one of `mul`, `div`", token)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr {
+    pub lhs: ::std::boxed::Box<MultExpr>,
+    pub rhs_s: ::std::vec::Vec<ExprRhsS>,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for Expr {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        Ok(Self {
+            lhs: ::tuckey::ast::from_child(token, "lhs")?,
+            rhs_s: ::tuckey::ast::from_child(token, "rhs_s")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExprRhsS {
+    pub oper: Oper,
+    pub rhs: ::std::boxed::Box<MultExpr>,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for ExprRhsS {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        Ok(Self {
+            oper: ::tuckey::ast::from_child(token, "oper")?,
+            rhs: ::tuckey::ast::from_child(token, "rhs")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultExpr {
+    pub lhs: ::std::boxed::Box<NumExpr>,
+    pub rhs_s: ::std::vec::Vec<MultExprRhsS>,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for MultExpr {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        Ok(Self {
+            lhs: ::tuckey::ast::from_child(token, "lhs")?,
+            rhs_s: ::tuckey::ast::from_child(token, "rhs_s")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultExprRhsS {
+    pub oper: MultOper,
+    pub rhs: ::std::boxed::Box<NumExpr>,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for MultExprRhsS {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        Ok(Self {
+            oper: ::tuckey::ast::from_child(token, "oper")?,
+            rhs: ::tuckey::ast::from_child(token, "rhs")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumExpr {
+    Int(PosInt),
+    Paren(NumExprParen),
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for NumExpr {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        match &token.data {
+            ::tuckey::token::TokenData::Branch(children) if children.len() == 1 => {
+                let (name, child) = &children[0];
+                match name.as_str() {
+                    "int" => Ok(NumExpr::Int(::tuckey::ast::FromToken::from_token(child)?)),
+                    "paren" => Ok(NumExpr::Paren(::tuckey::ast::FromToken::from_token(child)?)),
+                    _ => Err(::tuckey::ast::AstError::at("expected one of `int`, `paren`", token)),
+                }
+            }
+            _ => Err(::tuckey::ast::AstError::at("expected one of `int`, `paren`", token)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumExprParen {
+    pub expr: ::std::boxed::Box<Expr>,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for NumExprParen {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        Ok(Self {
+            expr: ::tuckey::ast::from_child(token, "expr")?,
+        })
+    }
+}
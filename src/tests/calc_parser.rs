@@ -0,0 +1,319 @@
+// Generated by tuckey from a grammar; do not edit.
+
+use ::tuckey::sequence::{ParseContext, RuleFn, TokenMatch};
+use ::tuckey::token::Token;
+
+pub const RULES: &[(&str, RuleFn)] = &[
+    ("nonzero", nonzero),
+    ("digit", digit),
+    ("posInt", pos_int),
+    ("oper", oper),
+    ("multOper", mult_oper),
+    ("expr", expr),
+    ("multExpr", mult_expr),
+    ("numExpr", num_expr),
+];
+
+pub fn nonzero<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("nonzero", pos, |ctx| ::tuckey::sequence::match_range(49, 57, tokens, pos, ctx))
+}
+
+pub fn digit<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("digit", pos, |ctx| ::tuckey::sequence::match_range(48, 57, tokens, pos, ctx))
+}
+
+pub fn pos_int<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("posInt", pos, |ctx| pos_int_1(tokens, pos, ctx))
+}
+
+fn pos_int_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(2);
+    let matched = nonzero(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = pos_int_2(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn pos_int_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = digit(tokens, pos + len, ctx) {
+        children.push(("".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+pub fn oper<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("oper", pos, |ctx| oper_1(tokens, pos, ctx))
+}
+
+fn oper_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = ::tuckey::sequence::match_raw("+", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "add", matched));
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("-", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "sub", matched));
+    }
+    None
+}
+
+pub fn mult_oper<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("multOper", pos, |ctx| mult_oper_1(tokens, pos, ctx))
+}
+
+fn mult_oper_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = ::tuckey::sequence::match_raw("*", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "mul", matched));
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("/", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "div", matched));
+    }
+    None
+}
+
+pub fn expr<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("expr", pos, |ctx| expr_1(tokens, pos, ctx))
+}
+
+fn expr_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(3);
+    let matched = mult_expr(tokens, pos + len, ctx)?;
+    children.push(("lhs".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = expr_2(tokens, pos + len, ctx)?;
+    children.push(("rhs_s".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn expr_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = expr_3(tokens, pos + len, ctx) {
+        children.push(("rhs_s".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn expr_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(5);
+    let matched = Some(::tuckey::sequence::rule_match("_", ::tuckey::sequence::match_nil()))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = oper(tokens, pos + len, ctx)?;
+    children.push(("oper".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = mult_expr(tokens, pos + len, ctx)?;
+    children.push(("rhs".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+pub fn mult_expr<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("multExpr", pos, |ctx| mult_expr_1(tokens, pos, ctx))
+}
+
+fn mult_expr_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(3);
+    let matched = num_expr(tokens, pos + len, ctx)?;
+    children.push(("lhs".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = mult_expr_2(tokens, pos + len, ctx)?;
+    children.push(("rhs_s".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn mult_expr_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = mult_expr_3(tokens, pos + len, ctx) {
+        children.push(("rhs_s".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn mult_expr_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(5);
+    let matched = Some(::tuckey::sequence::rule_match("_", ::tuckey::sequence::match_nil()))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = mult_oper(tokens, pos + len, ctx)?;
+    children.push(("oper".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = num_expr(tokens, pos + len, ctx)?;
+    children.push(("rhs".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+pub fn num_expr<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("numExpr", pos, |ctx| num_expr_1(tokens, pos, ctx))
+}
+
+fn num_expr_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = pos_int(tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "int", matched));
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    if let Some(matched) = num_expr_2(tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "paren", matched));
+    }
+    None
+}
+
+fn num_expr_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(5);
+    let matched = ::tuckey::sequence::match_raw("(", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = expr(tokens, pos + len, ctx)?;
+    children.push(("expr".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_raw(")", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
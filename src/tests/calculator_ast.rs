@@ -0,0 +1,41 @@
+use super::calc_ast::*;
+use crate::ast::*;
+use crate::corpus::*;
+use crate::meta::*;
+use test_case::test_case;
+
+fn eval_expr(expr: &Expr) -> f64 {
+    expr.rhs_s
+        .iter()
+        .fold(eval_mult_expr(&expr.lhs), |acc, rhs| match rhs.oper {
+            Oper::Add => acc + eval_mult_expr(&rhs.rhs),
+            Oper::Sub => acc - eval_mult_expr(&rhs.rhs),
+        })
+}
+
+fn eval_mult_expr(expr: &MultExpr) -> f64 {
+    expr.rhs_s
+        .iter()
+        .fold(eval_num_expr(&expr.lhs), |acc, rhs| match rhs.oper {
+            MultOper::Mul => acc * eval_num_expr(&rhs.rhs),
+            MultOper::Div => acc / eval_num_expr(&rhs.rhs),
+        })
+}
+
+fn eval_num_expr(expr: &NumExpr) -> f64 {
+    match expr {
+        NumExpr::Int(int) => int.parse().unwrap(),
+        NumExpr::Paren(paren) => eval_expr(&paren.expr),
+    }
+}
+
+#[test_case("1", 1.0; "number")]
+#[test_case("1 + 2 / 2", 2.0; "precedence")]
+#[test_case("(1 + 2) / 2", 1.5; "parens")]
+#[test_case("8 - 2 * (3 - 1)", 4.0; "nested")]
+pub fn eval_test(text: &str, expected: f64) {
+    let seqs = eval_rule_set(include_str!("calc.tuckey"));
+    let corpus = Corpus::make(text);
+    let token = seqs["expr"].parse_corpus(&corpus, &seqs).unwrap();
+    assert_eq!(eval_expr(&Expr::from_token(&token).unwrap()), expected);
+}
@@ -0,0 +1,84 @@
+use crate::corpus::*;
+use crate::meta::*;
+use crate::sequence::*;
+use crate::token::*;
+use test_case::test_case;
+
+pub fn calc_seqs() -> RefMap {
+    eval_rule_set(
+        "
+    nonzero = 1..9
+    digit = 0..9
+    posInt = nonzero & digit*
+    expr =
+        (expr:lhs + [+-]:oper + multExpr:rhs).binary |
+        multExpr.single
+    multExpr =
+        (multExpr:lhs + [*/]:oper + posInt:rhs).binary |
+        posInt.single
+    ",
+    )
+}
+
+pub fn eval(body: &str) -> Option<f64> {
+    let seqs = calc_seqs();
+    let seq = RefSeq::new("expr".to_string());
+    let matched = seq.parse_corpus(&Corpus::make(body), &seqs).ok();
+    matched.map(|t| eval_expr(&t))
+}
+
+pub fn eval_expr(expr: &Token<'_>) -> f64 {
+    match expr.get_first_child("binary") {
+        Some(binary) => {
+            let lhs = eval_expr(&binary.get_first_child("lhs").unwrap());
+            let rhs = eval_mult_expr(&binary.get_first_child("rhs").unwrap());
+            match binary.get_first_child("oper").unwrap().content() {
+                "+" => lhs + rhs,
+                _ => lhs - rhs,
+            }
+        }
+        None => eval_mult_expr(&expr.get_first_child("single").unwrap()),
+    }
+}
+
+pub fn eval_mult_expr(expr: &Token<'_>) -> f64 {
+    match expr.get_first_child("binary") {
+        Some(binary) => {
+            let lhs = eval_mult_expr(&binary.get_first_child("lhs").unwrap());
+            let rhs: f64 = str::parse(binary.get_first_child("rhs").unwrap().content()).unwrap();
+            match binary.get_first_child("oper").unwrap().content() {
+                "*" => lhs * rhs,
+                _ => lhs / rhs,
+            }
+        }
+        None => str::parse(expr.get_first_child("single").unwrap().content()).unwrap(),
+    }
+}
+
+#[test_case("1", Some(1.0); "number 1")]
+#[test_case("8 - 2 - 1", Some(5.0); "left associative minus")]
+#[test_case("8 / 2 / 2", Some(2.0); "left associative divide")]
+#[test_case("2 + 3 * 4 - 6 / 3", Some(12.0); "precedence")]
+#[test_case("2 + * 4", None; "missing operand")]
+pub fn eval_test(text: &str, expected: Option<f64>) {
+    assert_eq!(eval(text), expected);
+}
+
+#[test]
+pub fn indirect_test() {
+    let seqs = eval_rule_set(
+        "
+    a = (b & 'x') | 'y'
+    b = a
+    ",
+    );
+    let seq = RefSeq::new("a".to_string());
+    seq.assert_matches(&Corpus::make("yxxx"), &seqs, TokenMatchTestType::All);
+    seq.assert_matches(&Corpus::make("xy"), &seqs, TokenMatchTestType::None);
+
+    let mut ctx = ParseContext::new(&seqs).with_memo(MemoOptions::new());
+    let token = seq
+        .parse_corpus_with(&Corpus::make("yxx"), &mut ctx)
+        .unwrap();
+    assert_eq!(token.content(), "yxx");
+}
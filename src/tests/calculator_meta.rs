@@ -1,3 +1,5 @@
+use crate::action::*;
+use crate::ast::AstError;
 use crate::corpus::*;
 use crate::meta::*;
 use crate::sequence::*;
@@ -14,48 +16,44 @@ pub fn calc_seqs() -> RefMap {
     multOper = [*/]
     expr = 
         multExpr:lhs + 
-        (_ + oper:oper + multExpr:oper).rhs_s*:rhs_s
+        (_ + oper:oper + multExpr:rhs).rhs_s*:rhs_s
     multExpr = 
         numExpr:lhs + 
-        (_ + multOper:oper + numExpr:oper).rhs_s*:rhs_s
+        (_ + multOper:oper + numExpr:rhs).rhs_s*:rhs_s
     numExpr = posInt
     ",
     )
 }
 
-pub fn eval(body: &str) -> Option<f64> {
-    let seqs = calc_seqs();
-    let seq = seqs.get("expr").unwrap();
-    let matched = seq.match_tokens(&Corpus::make(body).tokens, &seqs);
-    matched.map(|t| eval_expr(&t.new_token))
+pub fn calc_actions() -> Actions<'static, f64> {
+    Actions::new()
+        .on("posInt", |int, _| match int.content().parse::<u32>() {
+            Ok(int) => Ok(int.into()),
+            Err(_) => Err(AstError::at("number too large", int)),
+        })
+        .on("expr", apply_opers)
+        .on("multExpr", apply_opers)
 }
 
-pub fn eval_expr(expr: &Token<'_>) -> f64 {
-    let mut to_ret = eval_mult_expr(&expr.get_first_child("lhs").unwrap());
-    for opers in expr.get_first_child("rhs_s").unwrap().get_children("rhs_s") {
-        match opers.get_children("oper")[0].content() {
-            "+" => to_ret += eval_mult_expr(&opers.get_children("oper")[1]),
-            "-" => to_ret -= eval_mult_expr(&opers.get_children("oper")[1]),
-            _ => {}
+// Folds `lhs (oper rhs)*` from the left.
+fn apply_opers(expr: &Token<'_>, mut values: Values<'_, f64>) -> Result<f64, AstError> {
+    let mut to_ret = values.take("lhs")?;
+    let opers = expr.select("rhs_s/rhs_s/oper").unwrap();
+    for (oper, rhs) in opers.iter().zip(values.take_all("rhs")) {
+        match oper.token.content() {
+            "+" => to_ret += rhs,
+            "-" => to_ret -= rhs,
+            "*" => to_ret *= rhs,
+            _ => to_ret /= rhs,
         }
     }
-    to_ret
+    Ok(to_ret)
 }
 
-pub fn eval_mult_expr(expr: &Token<'_>) -> f64 {
-    let mut to_ret = eval_num_expr(&expr.get_first_child("lhs").unwrap());
-    for opers in expr.get_first_child("rhs_s").unwrap().get_children("rhs_s") {
-        match opers.get_children("oper")[0].content() {
-            "*" => to_ret *= eval_num_expr(&opers.get_children("oper")[1]),
-            "/" => to_ret /= eval_num_expr(&opers.get_children("oper")[1]),
-            _ => {}
-        }
-    }
-    to_ret
-}
-
-pub fn eval_num_expr(expr: &Token<'_>) -> f64 {
-    str::parse(expr.content()).unwrap()
+pub fn eval(body: &str) -> Result<f64, ActionError> {
+    let seqs = calc_seqs();
+    let seq = RefSeq::new("expr".to_string());
+    calc_actions().parse(&seq, &Corpus::make(body), &seqs)
 }
 
 #[test]
@@ -88,5 +86,25 @@ pub fn expr_test() {
 #[test_case("1 + 2 / 2", Some(2.0); "number two")]
 #[test_case("1 / 2", Some(0.5); "number one-half")]
 pub fn eval_test(text: &str, expected: Option<f64>) {
-    assert_eq!(eval(text), expected);
+    assert_eq!(eval(text).ok(), expected);
+}
+
+#[test]
+pub fn action_error_test() {
+    assert_eq!(
+        eval("1 + 99999999999 * 2").unwrap_err().to_string(),
+        "number too large at 4..15"
+    );
+    assert!(matches!(eval("1 +"), Err(ActionError::Parse(_))));
+}
+
+#[test_case("1 + * 2", "expected multExpr at 1:5 while parsing expr"; "missing operand")]
+#[test_case("1 +\n 2 x", "expected one of multOper, oper, end of input at 2:4 while parsing expr > multExpr";
+"trailing garbage")]
+#[test_case("", "expected expr at 1:1"; "empty")]
+pub fn error_test(text: &str, message: &str) {
+    let seqs = calc_seqs();
+    let seq = RefSeq::new("expr".to_string());
+    let err = seq.parse_corpus(&Corpus::make(text), &seqs).unwrap_err();
+    assert_eq!(err.to_string(), message);
 }
@@ -0,0 +1,150 @@
+use crate::corpus::*;
+use crate::meta::*;
+use crate::sequence::*;
+use crate::tests::{calc_parser, features_parser, left_parser};
+use crate::token::*;
+use test_case::test_case;
+
+// Parses `text` with every rule of `grammar`, interpreted and compiled, and
+// checks that both give the same tree or the same error.
+fn assert_same(grammar: &str, rules: &[(&str, RuleFn)], text: &str) {
+    let seqs = eval_rule_set(grammar);
+    let corpus = Corpus::make(text);
+    for (name, rule) in rules {
+        let interpreted = RefSeq::new(name.to_string());
+        let compiled = CompiledSeq(*rule);
+        assert_eq!(
+            interpreted
+                .match_tokens(&corpus.tokens, &seqs)
+                .map(|m| (m.len, m.new_token.graph())),
+            compiled
+                .match_first(&corpus)
+                .map(|m| (m.len, m.new_token.graph())),
+            "rule {name} on {text:?}"
+        );
+        assert_eq!(
+            interpreted
+                .parse_corpus(&corpus, &seqs)
+                .map(|t| t.to_sexpr())
+                .map_err(|e| e.to_string()),
+            compiled
+                .parse(&corpus)
+                .map(|t| t.to_sexpr())
+                .map_err(|e| e.to_string()),
+            "rule {name} on {text:?}"
+        );
+    }
+}
+
+#[test_case("1"; "number")]
+#[test_case("1 + 2 * (3 - 4) / 5"; "nested")]
+#[test_case("1 + * 2"; "missing operand")]
+#[test_case("1 +\n 2 x"; "trailing garbage")]
+#[test_case("(1 + 2"; "unclosed paren")]
+#[test_case(""; "empty")]
+pub fn calc_test(text: &str) {
+    assert_same(include_str!("calc.tuckey"), calc_parser::RULES, text);
+}
+
+#[test_case("8 / 2 / 2"; "left associative")]
+#[test_case("2 + 3 * 4 - 6 / 3"; "precedence")]
+#[test_case("2 + * 4"; "missing operand")]
+#[test_case("yxxx"; "indirect")]
+#[test_case("xy"; "indirect failure")]
+pub fn left_test(text: &str) {
+    assert_same(include_str!("left.tuckey"), left_parser::RULES, text);
+}
+
+#[test_case("(1+2)"; "cut")]
+#[test_case("(1+"; "failed cut")]
+#[test_case("abc"; "lookahead")]
+#[test_case("abc1"; "failed lookahead")]
+#[test_case("0xbeef"; "repeat")]
+#[test_case("0x1"; "too few repeats")]
+#[test_case("0x12345"; "repeat stops at max")]
+#[test_case("(1,23,4)"; "separated")]
+#[test_case("(1,)"; "trailing separator")]
+#[test_case("1;2;"; "separated with trailing")]
+#[test_case("a"; "empty repeats")]
+#[test_case("b"; "failed empty repeat")]
+pub fn features_test(text: &str) {
+    assert_same(
+        include_str!("features.tuckey"),
+        features_parser::RULES,
+        text,
+    );
+}
+
+#[test]
+pub fn memo_test() {
+    let corpus = Corpus::make("2 + 3 * 4 - 6 / 3");
+    let mut ctx = ParseContext::without_refs().with_memo(MemoOptions::new());
+    let token = CompiledSeq(left_parser::expr)
+        .parse_with(&corpus, &mut ctx)
+        .unwrap();
+    assert_eq!(token.content(), "2 + 3 * 4 - 6 / 3");
+}
+
+#[test]
+pub fn mixed_test() {
+    // Compiled rules can sit in a `RefMap` next to interpreted ones, under
+    // their own name or another.
+    let mut seqs = eval_rule_set("sum = int & ('+' & int)*");
+    seqs.insert(
+        "int".to_string(),
+        Box::new(CompiledSeq(calc_parser::pos_int)),
+    );
+    let seq = RefSeq::new("sum".to_string());
+    let token = seq.parse_corpus(&Corpus::make("12+3"), &seqs).unwrap();
+    assert_eq!(token.content(), "12+3");
+
+    let mut seqs = RefMap::new();
+    seqs.insert("expr".to_string(), Box::new(CompiledSeq(left_parser::expr)));
+    let corpus = Corpus::make("1+2-3");
+    let expr = RefSeq::new("expr".to_string());
+    let token = expr.parse_corpus(&corpus, &seqs).unwrap();
+    let compiled = CompiledSeq(left_parser::expr).parse(&corpus).unwrap();
+    assert_eq!(token.graph(), compiled.graph());
+}
+
+#[test_case("1+2;3*;4;"; "skips to separator")]
+#[test_case("1;2"; "skips to end")]
+#[test_case("1;;"; "skips separator alone")]
+pub fn recover_test(text: &str) {
+    let seqs = eval_rule_set(include_str!("features.tuckey"));
+    let corpus = Corpus::make(text);
+    let stmts = RefSeq::new("stmts".to_string());
+    let interpreted = stmts.parse_recovering(&corpus, &seqs);
+    let compiled = CompiledSeq(features_parser::stmts).parse_recovering(&corpus);
+    assert_eq!(interpreted.token.graph(), compiled.token.graph());
+    assert_eq!(interpreted.errors, compiled.errors);
+    assert!(!interpreted.errors.is_empty());
+}
+
+#[test_case("{a=1;}", &[]; "valid")]
+#[test_case("{a=1;b=;c=2;}", &["b=;"]; "skips one item")]
+#[test_case("{}", &[]; "empty")]
+pub fn recover_inside_test(text: &str, skipped: &[&str]) {
+    // The list ends at `}` as usual instead of recovering through it.
+    let seqs = eval_rule_set(include_str!("features.tuckey"));
+    let corpus = Corpus::make(text);
+    let block = RefSeq::new("block".to_string());
+    let interpreted = block.parse_recovering(&corpus, &seqs);
+    let compiled = CompiledSeq(features_parser::block).parse_recovering(&corpus);
+    assert_eq!(interpreted.token.graph(), compiled.token.graph());
+    assert_eq!(interpreted.errors, compiled.errors);
+    assert_eq!(interpreted.errors.len(), skipped.len());
+    let errors: Vec<&str> = interpreted
+        .token
+        .descendants()
+        .filter(|t| t.error().is_some())
+        .map(|t| t.content())
+        .collect();
+    assert_eq!(errors, skipped);
+    let last = interpreted.token.entries().last().unwrap();
+    assert_eq!(last.1.content(), "}");
+    if skipped.is_empty() {
+        let parsed = block.parse_corpus(&corpus, &seqs).unwrap();
+        assert_eq!(interpreted.token.graph(), parsed.graph());
+    }
+}
@@ -0,0 +1,738 @@
+// Generated by tuckey from a grammar; do not edit.
+
+use ::tuckey::sequence::{ParseContext, RuleFn, TokenMatch};
+use ::tuckey::token::Token;
+
+pub const RULES: &[(&str, RuleFn)] = &[
+    ("nonzero", nonzero),
+    ("digit", digit),
+    ("posInt", pos_int),
+    ("expr", expr),
+    ("stmts", stmts),
+    ("paren", paren),
+    ("ident", ident),
+    ("whole", whole),
+    ("code", code),
+    ("args", args),
+    ("list", list),
+    ("block", block),
+    ("peeks", peeks),
+];
+
+pub fn nonzero<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("nonzero", pos, |ctx| ::tuckey::sequence::match_range(49, 57, tokens, pos, ctx))
+}
+
+pub fn digit<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("digit", pos, |ctx| ::tuckey::sequence::match_range(48, 57, tokens, pos, ctx))
+}
+
+pub fn pos_int<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("posInt", pos, |ctx| pos_int_1(tokens, pos, ctx))
+}
+
+fn pos_int_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(2);
+    let matched = nonzero(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = pos_int_2(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn pos_int_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = digit(tokens, pos + len, ctx) {
+        children.push(("".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+pub fn expr<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("expr", pos, |ctx| expr_1(tokens, pos, ctx))
+}
+
+fn expr_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(2);
+    let matched = pos_int(tokens, pos + len, ctx)?;
+    children.push(("lhs".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = expr_2(tokens, pos + len, ctx)?;
+    children.push(("rhs_s".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn expr_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = expr_3(tokens, pos + len, ctx) {
+        children.push(("rhs_s".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn expr_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(2);
+    let matched = expr_4(tokens, pos + len, ctx)?;
+    children.push(("oper".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = pos_int(tokens, pos + len, ctx)?;
+    children.push(("rhs".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn expr_4<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = ::tuckey::sequence::match_raw("+", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "+", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("-", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "-", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("*", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(2, "*", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("/", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(3, "/", matched));
+    }
+    None
+}
+
+pub fn stmts<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("stmts", pos, |ctx| stmts_1(tokens, pos, ctx))
+}
+
+fn stmts_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = stmts_2(tokens, pos + len, ctx) {
+        children.push(("stmt".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn stmts_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_recover(
+        tokens,
+        pos,
+        ctx,
+        |ctx| stmts_3(tokens, pos, ctx),
+        |ctx, at| ::tuckey::sequence::match_raw(";", tokens, at, ctx).map(|m| m.len),
+    )
+}
+
+fn stmts_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(2);
+    let matched = expr(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_raw(";", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+pub fn paren<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("paren", pos, |ctx| paren_1(tokens, pos, ctx))
+}
+
+fn paren_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = paren_2(tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "group", matched));
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    if let Some(matched) = pos_int(tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "int", matched));
+    }
+    None
+}
+
+fn paren_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(3);
+    let matched = ::tuckey::sequence::match_raw("(", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    ctx.commit(tokens, |ctx| {
+        let matched = expr(tokens, pos + len, ctx)?;
+        children.push(("inner".to_string(), matched.new_token));
+        len += matched.len;
+        let matched = ::tuckey::sequence::match_raw(")", tokens, pos + len, ctx)?;
+        children.push(("".to_string(), matched.new_token));
+        len += matched.len;
+        Some(())
+    })?;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+pub fn ident<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("ident", pos, |ctx| ident_1(tokens, pos, ctx))
+}
+
+fn ident_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(3);
+    let matched = ident_2(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ident_3(tokens, pos + len, ctx)?;
+    children.push(("name".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ident_4(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn ident_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_lookahead(tokens, pos, ctx, false, |ctx| {
+        ::tuckey::sequence::match_range(97, 122, tokens, pos, ctx).is_some()
+    })
+}
+
+fn ident_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = ::tuckey::sequence::match_range(97, 122, tokens, pos + len, ctx) {
+        children.push(("".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if children.is_empty() || ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn ident_4<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_lookahead(tokens, pos, ctx, true, |ctx| {
+        ::tuckey::sequence::match_range(48, 57, tokens, pos, ctx).is_some()
+    })
+}
+
+pub fn whole<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("whole", pos, |ctx| whole_1(tokens, pos, ctx))
+}
+
+fn whole_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(2);
+    let matched = expr(tokens, pos + len, ctx)?;
+    children.push(("expr".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = whole_2(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn whole_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_lookahead(tokens, pos, ctx, true, |ctx| {
+        ctx.call_plain_rule("any", pos, |ctx| ::tuckey::sequence::match_any(tokens, pos, ctx)).is_some()
+    })
+}
+
+pub fn code<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("code", pos, |ctx| code_1(tokens, pos, ctx))
+}
+
+fn code_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(3);
+    let matched = code_2(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = code_3(tokens, pos + len, ctx)?;
+    children.push(("digits".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = code_5(tokens, pos + len, ctx)?;
+    children.push(("pad".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn code_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(2);
+    let matched = ::tuckey::sequence::match_raw("0", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_raw("x", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn code_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_repeat(
+        tokens,
+        pos,
+        ctx,
+        "hex",
+        2,
+        Some(4),
+        |ctx, at| code_4(tokens, at, ctx),
+    )
+}
+
+fn code_4<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = ::tuckey::sequence::match_raw("0", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "0", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("1", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "1", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("2", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(2, "2", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("3", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(3, "3", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("4", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(4, "4", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("5", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(5, "5", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("6", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(6, "6", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("7", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(7, "7", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("8", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(8, "8", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("9", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(9, "9", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("a", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(10, "a", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("b", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(11, "b", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("c", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(12, "c", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("d", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(13, "d", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("e", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(14, "e", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("f", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(15, "f", matched));
+    }
+    None
+}
+
+fn code_5<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_repeat(
+        tokens,
+        pos,
+        ctx,
+        "",
+        1,
+        None,
+        |_, _| Some(::tuckey::sequence::rule_match("_", ::tuckey::sequence::match_nil())),
+    )
+}
+
+pub fn args<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("args", pos, |ctx| args_1(tokens, pos, ctx))
+}
+
+fn args_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(3);
+    let matched = ::tuckey::sequence::match_raw("(", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = args_2(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_raw(")", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn args_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_separated(
+        tokens,
+        pos,
+        ctx,
+        "arg",
+        Some("comma"),
+        1,
+        false,
+        |ctx, at| pos_int(tokens, at, ctx),
+        |ctx, at| ::tuckey::sequence::match_raw(",", tokens, at, ctx),
+    )
+}
+
+pub fn list<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("list", pos, |ctx| list_1(tokens, pos, ctx))
+}
+
+fn list_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_separated(
+        tokens,
+        pos,
+        ctx,
+        "item",
+        None,
+        0,
+        true,
+        |ctx, at| pos_int(tokens, at, ctx),
+        |ctx, at| ::tuckey::sequence::match_raw(";", tokens, at, ctx),
+    )
+}
+
+pub fn block<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("block", pos, |ctx| block_1(tokens, pos, ctx))
+}
+
+fn block_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(3);
+    let matched = ::tuckey::sequence::match_raw("{", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = block_2(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_raw("}", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn block_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = block_3(tokens, pos + len, ctx) {
+        children.push(("stmt".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn block_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_recover(
+        tokens,
+        pos,
+        ctx,
+        |ctx| block_4(tokens, pos, ctx),
+        |ctx, at| ::tuckey::sequence::match_raw(";", tokens, at, ctx).map(|m| m.len),
+    )
+}
+
+fn block_4<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(4);
+    let matched = ::tuckey::sequence::match_range(97, 122, tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_raw("=", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_range(48, 57, tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_raw(";", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+pub fn peeks<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("peeks", pos, |ctx| peeks_1(tokens, pos, ctx))
+}
+
+fn peeks_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(3);
+    let matched = peeks_2(tokens, pos + len, ctx)?;
+    children.push(("none".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = peeks_4(tokens, pos + len, ctx)?;
+    children.push(("one".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_raw("a", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn peeks_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = peeks_3(tokens, pos + len, ctx) {
+        children.push(("".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn peeks_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_lookahead(tokens, pos, ctx, false, |ctx| {
+        ::tuckey::sequence::match_raw("a", tokens, pos, ctx).is_some()
+    })
+}
+
+fn peeks_4<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = peeks_5(tokens, pos + len, ctx) {
+        children.push(("".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if children.is_empty() || ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn peeks_5<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ::tuckey::sequence::match_lookahead(tokens, pos, ctx, false, |ctx| {
+        ::tuckey::sequence::match_raw("a", tokens, pos, ctx).is_some()
+    })
+}
@@ -0,0 +1,251 @@
+// Generated by tuckey from a grammar; do not edit.
+
+use ::tuckey::sequence::{ParseContext, RuleFn, TokenMatch};
+use ::tuckey::token::Token;
+
+pub const RULES: &[(&str, RuleFn)] = &[
+    ("nonzero", nonzero),
+    ("digit", digit),
+    ("posInt", pos_int),
+    ("expr", expr),
+    ("multExpr", mult_expr),
+    ("a", a),
+    ("b", b),
+];
+
+pub fn nonzero<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("nonzero", pos, |ctx| ::tuckey::sequence::match_range(49, 57, tokens, pos, ctx))
+}
+
+pub fn digit<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("digit", pos, |ctx| ::tuckey::sequence::match_range(48, 57, tokens, pos, ctx))
+}
+
+pub fn pos_int<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_plain_rule("posInt", pos, |ctx| pos_int_1(tokens, pos, ctx))
+}
+
+fn pos_int_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(2);
+    let matched = nonzero(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = pos_int_2(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn pos_int_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = vec![];
+    while let Some(matched) = digit(tokens, pos + len, ctx) {
+        children.push(("".to_string(), matched.new_token));
+        if matched.len == 0 {
+            break;
+        }
+        len += matched.len;
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+pub fn expr<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_rule("expr", pos, |ctx| expr_1(tokens, pos, ctx))
+}
+
+fn expr_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = expr_2(tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "binary", matched));
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    if let Some(matched) = mult_expr(tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "single", matched));
+    }
+    None
+}
+
+fn expr_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(5);
+    let matched = expr(tokens, pos + len, ctx)?;
+    children.push(("lhs".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = expr_3(tokens, pos + len, ctx)?;
+    children.push(("oper".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = mult_expr(tokens, pos + len, ctx)?;
+    children.push(("rhs".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn expr_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = ::tuckey::sequence::match_raw("+", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "+", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("-", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "-", matched));
+    }
+    None
+}
+
+pub fn mult_expr<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_rule("multExpr", pos, |ctx| mult_expr_1(tokens, pos, ctx))
+}
+
+fn mult_expr_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = mult_expr_2(tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "binary", matched));
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    if let Some(matched) = pos_int(tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "single", matched));
+    }
+    None
+}
+
+fn mult_expr_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(5);
+    let matched = mult_expr(tokens, pos + len, ctx)?;
+    children.push(("lhs".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = mult_expr_3(tokens, pos + len, ctx)?;
+    children.push(("oper".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = Some(::tuckey::sequence::rule_match("ws", ::tuckey::sequence::match_whitespace(tokens, pos + len)))?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = pos_int(tokens, pos + len, ctx)?;
+    children.push(("rhs".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+fn mult_expr_3<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = ::tuckey::sequence::match_raw("*", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "*", matched));
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("/", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "/", matched));
+    }
+    None
+}
+
+pub fn a<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_rule("a", pos, |ctx| a_1(tokens, pos, ctx))
+}
+
+fn a_1<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    if let Some(matched) = a_2(tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(0, "", matched));
+    }
+    if ctx.cut_failed() {
+        return None;
+    }
+    if let Some(matched) = ::tuckey::sequence::match_raw("y", tokens, pos, ctx) {
+        return Some(::tuckey::sequence::choose_match(1, "", matched));
+    }
+    None
+}
+
+fn a_2<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    let mut len = 0;
+    let mut children = Vec::with_capacity(2);
+    let matched = b(tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    let matched = ::tuckey::sequence::match_raw("x", tokens, pos + len, ctx)?;
+    children.push(("".to_string(), matched.new_token));
+    len += matched.len;
+    Some(::tuckey::sequence::branch_at(tokens, pos, len, children))
+}
+
+pub fn b<'a>(
+    tokens: &[Token<'a>],
+    pos: usize,
+    ctx: &mut ParseContext<'a>,
+) -> Option<TokenMatch<'a>> {
+    ctx.call_rule("b", pos, |ctx| a(tokens, pos, ctx))
+}
@@ -1,2 +1,16 @@
+#[rustfmt::skip]
+pub mod calc_ast;
+#[rustfmt::skip]
+pub mod calc_parser;
 pub mod calculator;
+pub mod calculator_ast;
+pub mod calculator_left;
 pub mod calculator_meta;
+pub mod compiled;
+#[rustfmt::skip]
+pub mod features_parser;
+#[rustfmt::skip]
+pub mod left_parser;
+pub mod names;
+#[rustfmt::skip]
+pub mod names_ast;
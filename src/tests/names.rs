@@ -0,0 +1,38 @@
+use crate::ast::*;
+use crate::corpus::*;
+use crate::meta::*;
+use crate::sequence::*;
+use crate::tests::names_ast;
+
+#[test]
+pub fn std_names_test() {
+    // Rules named like std types get types of the same name, which the rest
+    // of the generated code does not mistake for the std ones.
+    let seqs = eval_rule_set(include_str!("names.tuckey"));
+    let result = RefSeq::new("result".to_string());
+    let parse = |text| {
+        let token = result.parse_corpus(&Corpus::make(text), &seqs).unwrap();
+        names_ast::Result::from_token(&token).unwrap()
+    };
+    let word = |body: &str| names_ast::String {
+        body: body.to_string(),
+    };
+    assert_eq!(
+        parse("[\"ab\"\"c\"]"),
+        names_ast::Result::List(names_ast::Vec {
+            item: vec![word("ab"), word("c")],
+        })
+    );
+    assert_eq!(
+        parse("?"),
+        names_ast::Result::Opt(names_ast::Option { value: None })
+    );
+    assert_eq!(
+        parse("((\"x\"))"),
+        names_ast::Result::Boxed(names_ast::Box::Nested(names_ast::BoxNested {
+            inner: Box::new(names_ast::Box::Nested(names_ast::BoxNested {
+                inner: Box::new(names_ast::Box::Leaf(word("x"))),
+            })),
+        }))
+    );
+}
@@ -0,0 +1,113 @@
+// Generated by tuckey from a grammar; do not edit.
+
+pub type Word = ::std::string::String;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct String {
+    pub body: Word,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for String {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        Ok(Self {
+            body: ::tuckey::ast::from_child(token, "body")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vec {
+    pub item: ::std::vec::Vec<String>,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for Vec {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        Ok(Self {
+            item: ::tuckey::ast::from_child(token, "item")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Option {
+    pub value: ::core::option::Option<String>,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for Option {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        Ok(Self {
+            value: ::tuckey::ast::from_child(token, "value")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Box {
+    Nested(BoxNested),
+    Leaf(String),
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for Box {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        match &token.data {
+            ::tuckey::token::TokenData::Branch(children) if children.len() == 1 => {
+                let (name, child) = &children[0];
+                match name.as_str() {
+                    "nested" => Ok(Box::Nested(::tuckey::ast::FromToken::from_token(child)?)),
+                    "leaf" => Ok(Box::Leaf(::tuckey::ast::FromToken::from_token(child)?)),
+                    _ => Err(::tuckey::ast::AstError::at("expected one of `nested`, `leaf`", token)),
+                }
+            }
+            _ => Err(::tuckey::ast::AstError::at("expected one of `nested`, `leaf`", token)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxNested {
+    pub inner: ::std::boxed::Box<Box>,
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for BoxNested {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        Ok(Self {
+            inner: ::tuckey::ast::from_child(token, "inner")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Result {
+    List(Vec),
+    Opt(Option),
+    Boxed(Box),
+}
+
+impl<'a> ::tuckey::ast::FromToken<'a> for Result {
+    fn from_token(
+        token: &::tuckey::token::Token<'a>,
+    ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+        match &token.data {
+            ::tuckey::token::TokenData::Branch(children) if children.len() == 1 => {
+                let (name, child) = &children[0];
+                match name.as_str() {
+                    "list" => Ok(Result::List(::tuckey::ast::FromToken::from_token(child)?)),
+                    "opt" => Ok(Result::Opt(::tuckey::ast::FromToken::from_token(child)?)),
+                    "boxed" => Ok(Result::Boxed(::tuckey::ast::FromToken::from_token(child)?)),
+                    _ => Err(::tuckey::ast::AstError::at("expected one of `list`, `opt`, `boxed`", token)),
+                }
+            }
+            _ => Err(::tuckey::ast::AstError::at("expected one of `list`, `opt`, `boxed`", token)),
+        }
+    }
+}
@@ -1,85 +1,312 @@
-use core::ops::Range;
+use core::ops::{Index, Range};
 use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::sequence::ParseError;
+use crate::source::*;
+
+mod owned;
+pub use owned::*;
+
+#[cfg(test)]
+mod tests;
 
 #[derive(Clone)]
 pub struct Token<'a> {
     pub source: &'a str,
+    pub file: FileId,
+    // The outermost rule whose match this branch is, and for the branch left
+    // by a `ChooseSeq`, the index of the alternative that matched.
+    pub rule: Option<&'a str>,
+    pub alt: Option<usize>,
+    // Where the value an action made from this match is kept on the
+    // `ParseContext` that parsed it (see `action`).
+    pub value: Option<usize>,
     pub data: TokenData<'a>,
 }
 
+// `Error` stands in for input skipped while recovering from a syntax error
+// (see `RecoverSeq`), with the error that caused it. Children are shared, so
+// that cloning a match, as the memo table does, costs the same at any size.
 #[derive(Debug, Clone)]
 pub enum TokenData<'a> {
     Leaf(Range<usize>),
-    Branch(Vec<(String, Token<'a>)>),
+    Branch(Arc<[(String, Token<'a>)]>),
+    Error(Range<usize>, Box<ParseError>),
 }
 
-impl<'a> Token<'a> {
-    pub fn content_range(&self) -> Option<Range<usize>> {
-        match &self.data {
-            TokenData::Leaf(range) => Some(range.clone()),
-            TokenData::Branch(children) if children.is_empty() => None,
-            TokenData::Branch(children) => {
-                let iter: Vec<Range<usize>> = children
-                    .iter()
-                    .filter_map(|s| s.1.content_range())
-                    .collect();
-                iter.first()
-                    .map(|first| first.start..iter.last().unwrap().end)
+// The navigation API shared by `Token` and `OwnedToken`. Implementors expose
+// their parts; everything else is built on those once.
+pub trait TokenNode: Sized {
+    fn source(&self) -> &str;
+    fn file(&self) -> FileId;
+    fn rule(&self) -> Option<&str>;
+    fn alt(&self) -> Option<usize>;
+    // The range of a leaf or error node; branches have none of their own.
+    fn range(&self) -> Option<Range<usize>>;
+    fn error(&self) -> Option<&ParseError>;
+    // Every child with its name, in order; leaves have none.
+    fn entries(&self) -> &[(String, Self)];
+
+    // Branches whose leaves come from more than one source have no single
+    // range; use `spans` for those.
+    fn content_range(&self) -> Option<Range<usize>> {
+        match self.range() {
+            Some(range) => Some(range),
+            None => match self.spans().as_slice() {
+                [span] => Some(span.range.clone()),
+                _ => None,
+            },
+        }
+    }
+
+    fn spans(&self) -> Vec<Span<'_>> {
+        let mut spans = vec![];
+        for token in self.descendants() {
+            let Some(range) = token.range().filter(|range| !range.is_empty()) else {
+                continue;
+            };
+            match spans.last_mut() {
+                Some(Span {
+                    file,
+                    source,
+                    range: last,
+                }) if *file == token.file() && std::ptr::eq(*source, token.source()) => {
+                    last.end = range.end
+                }
+                _ => spans.push(Span {
+                    file: token.file(),
+                    source: token.source(),
+                    range,
+                }),
             }
         }
+        spans
     }
 
-    pub fn content(&self) -> &str {
-        self.content_range().map_or_else(|| "", |r| &self.source[r])
+    fn content(&self) -> &str {
+        self.content_range()
+            .map_or_else(|| "", |r| &self.source()[r])
     }
 
-    pub fn get_children(&'a self, key: &'a str) -> Vec<Token<'a>> {
-        match &self.data {
-            TokenData::Leaf(_) => vec![],
-            TokenData::Branch(children) => children
-                .iter()
-                .filter(|i| i.0 == key)
-                .map(|i| i.1.clone())
-                .collect(),
+    fn start_position(&self, map: &SourceMap<'_>, unit: ColumnUnit) -> Option<Position> {
+        self.content_range().map(|r| map.position(r.start, unit))
+    }
+
+    fn end_position(&self, map: &SourceMap<'_>, unit: ColumnUnit) -> Option<Position> {
+        self.content_range().map(|r| map.position(r.end, unit))
+    }
+
+    // The errors recovered from anywhere in this tree, in input order.
+    fn errors(&self) -> Vec<&ParseError> {
+        self.descendants()
+            .filter_map(|token| token.error())
+            .collect()
+    }
+
+    fn children(&self) -> impl DoubleEndedIterator<Item = &Self> + ExactSizeIterator {
+        self.entries().iter().map(|(_, child)| child)
+    }
+
+    fn children_named<'t>(&'t self, key: &'t str) -> impl Iterator<Item = &'t Self> + 't {
+        self.entries()
+            .iter()
+            .filter(move |(name, _)| name == key)
+            .map(|(_, child)| child)
+    }
+
+    fn child(&self, key: &str) -> Option<&Self> {
+        self.entries()
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, child)| child)
+    }
+
+    fn child_at(&self, index: usize) -> Option<&Self> {
+        self.entries().get(index).map(|(_, child)| child)
+    }
+
+    fn name_at(&self, index: usize) -> Option<&str> {
+        self.entries().get(index).map(|(name, _)| name.as_str())
+    }
+
+    // This token and everything under it, parents before their children.
+    fn descendants(&self) -> Descendants<'_, Self> {
+        Descendants { stack: vec![self] }
+    }
+
+    // This token and everything under it, children before their parents.
+    fn descendants_post_order(&self) -> PostOrder<'_, Self> {
+        PostOrder {
+            stack: vec![(self, 0)],
+        }
+    }
+
+    fn graph(&self) -> String {
+        "\n".to_string() + &graph_depth(self, 0)
+    }
+}
+
+fn graph_depth(token: &impl TokenNode, depth: usize) -> String {
+    let mut graph = String::new();
+    for _ in 0..depth {
+        graph += "\t";
+    }
+    // `expr {` for a branch built by `expr`, `expr|1 {` if it took the
+    // second alternative.
+    if let Some(rule) = token.rule() {
+        graph += rule;
+    }
+    if let Some(alt) = token.alt() {
+        graph += &format!("|{alt}");
+    }
+    if token.rule().is_some() || token.alt().is_some() {
+        graph += " ";
+    }
+    match (token.range(), token.error()) {
+        (Some(_), None) => {
+            graph += &format!("{:?}", token.content());
+        }
+        (_, Some(_)) => {
+            graph += &format!("ERROR {:?}", token.content());
+        }
+        (None, None) => {
+            graph += "{\n";
+            for (name, child) in token.entries() {
+                if !name.is_empty() {
+                    for _ in 0..depth + 1 {
+                        graph += "\t";
+                    }
+                    graph += &format!("{name}:\n{},\n", graph_depth(child, depth + 1));
+                } else {
+                    graph += &format!("{},\n", graph_depth(child, depth + 1));
+                }
+            }
+            for _ in 0..depth {
+                graph += "\t";
+            }
+            graph += "}";
         }
     }
+    graph
+}
+
+impl TokenNode for Token<'_> {
+    fn source(&self) -> &str {
+        self.source
+    }
 
-    pub fn get_first_child(&'a self, key: &'a str) -> Option<Token<'a>> {
-        self.get_children(key).get(0).cloned()
+    fn file(&self) -> FileId {
+        self.file
     }
 
-    pub fn graph(&self) -> String {
-        "\n".to_string() + &self.graph_depth(0)
+    fn rule(&self) -> Option<&str> {
+        self.rule
+    }
+
+    fn alt(&self) -> Option<usize> {
+        self.alt
+    }
+
+    fn range(&self) -> Option<Range<usize>> {
+        match &self.data {
+            TokenData::Leaf(range) | TokenData::Error(range, _) => Some(range.clone()),
+            TokenData::Branch(_) => None,
+        }
     }
 
-    fn graph_depth(&self, depth: usize) -> String {
-        let mut graph = String::new();
-        for _ in 0..depth {
-            graph += "\t";
+    fn error(&self) -> Option<&ParseError> {
+        match &self.data {
+            TokenData::Error(_, error) => Some(error),
+            _ => None,
         }
+    }
+
+    fn entries(&self) -> &[(String, Self)] {
         match &self.data {
-            TokenData::Leaf(_) => {
-                graph += &format!("{:?}", self.content());
+            TokenData::Leaf(_) | TokenData::Error(..) => &[],
+            TokenData::Branch(children) => children,
+        }
+    }
+}
+
+impl<'a> Token<'a> {
+    pub fn get_children(&self, key: &str) -> Vec<Token<'a>> {
+        self.children_named(key).cloned().collect()
+    }
+
+    pub fn get_first_child(&self, key: &str) -> Option<Token<'a>> {
+        self.child(key).cloned()
+    }
+}
+
+macro_rules! index_children {
+    ($($ty:ty),*) => {
+        $(
+            impl Index<usize> for $ty {
+                type Output = Self;
+
+                fn index(&self, index: usize) -> &Self::Output {
+                    match self.child_at(index) {
+                        Some(child) => child,
+                        None => panic!("no child at index {index} of {self:?}"),
+                    }
+                }
             }
-            TokenData::Branch(children) => {
-                graph += "{\n";
-                for child in children {
-                    if !child.0.is_empty() {
-                        for _ in 0..depth + 1 {
-                            graph += "\t";
-                        }
-                        graph += &format!("{0}:\n{1},\n", child.0, child.1.graph_depth(depth + 1));
-                    } else {
-                        graph += &format!("{},\n", child.1.graph_depth(depth + 1));
+
+            impl Index<&str> for $ty {
+                type Output = Self;
+
+                fn index(&self, key: &str) -> &Self::Output {
+                    match self.child(key) {
+                        Some(child) => child,
+                        None => panic!("no child named {key:?} in {self:?}"),
                     }
                 }
-                for _ in 0..depth {
-                    graph += "\t";
+            }
+        )*
+    };
+}
+
+index_children!(Token<'_>, OwnedToken);
+
+pub struct Descendants<'t, T> {
+    stack: Vec<&'t T>,
+}
+
+impl<'t, T: TokenNode> Iterator for Descendants<'t, T> {
+    type Item = &'t T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.stack.pop()?;
+        self.stack.extend(token.children().rev());
+        Some(token)
+    }
+}
+
+// Each entry is a token and how many of its children have been visited.
+pub struct PostOrder<'t, T> {
+    stack: Vec<(&'t T, usize)>,
+}
+
+impl<'t, T: TokenNode> Iterator for PostOrder<'t, T> {
+    type Item = &'t T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (token, visited) = self.stack.last_mut()?;
+            let token = *token;
+            match token.child_at(*visited) {
+                Some(child) => {
+                    *visited += 1;
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    self.stack.pop();
+                    return Some(token);
                 }
-                graph += "}";
             }
         }
-        graph
     }
 }
 
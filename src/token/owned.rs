@@ -0,0 +1,162 @@
+use core::ops::Range;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use crate::sequence::ParseError;
+use crate::token::*;
+
+// A token that owns its source through an `Arc`, so that it can outlive the
+// buffer it was parsed from and be sent between threads. Every token taken
+// from the same source shares one copy of it.
+#[derive(Clone)]
+pub struct OwnedToken {
+    pub source: Arc<str>,
+    pub file: FileId,
+    pub rule: Option<Arc<str>>,
+    pub alt: Option<usize>,
+    pub data: OwnedTokenData,
+}
+
+#[derive(Debug, Clone)]
+pub enum OwnedTokenData {
+    Leaf(Range<usize>),
+    Branch(Vec<(String, OwnedToken)>),
+    Error(Range<usize>, Box<ParseError>),
+}
+
+impl Token<'_> {
+    // Copies each source the tree refers to once.
+    pub fn to_owned_token(&self) -> OwnedToken {
+        self.to_owned_token_in(&[])
+    }
+
+    // Shares any of `sources` that the tree refers to, by address, instead of
+    // copying them; pass the `Arc` the text was parsed from to convert
+    // without copying anything.
+    pub fn to_owned_token_in(&self, sources: &[Arc<str>]) -> OwnedToken {
+        let mut copies = sources.iter().map(|s| (&**s, s.clone())).collect();
+        self.to_owned_with(&mut copies)
+    }
+
+    // Rule names are shared through `copies` like sources.
+    fn to_owned_with<'s>(&'s self, copies: &mut Vec<(&'s str, Arc<str>)>) -> OwnedToken {
+        let source = share(copies, self.source);
+        let rule = self.rule.map(|rule| share(copies, rule));
+        let data = match &self.data {
+            TokenData::Leaf(range) => OwnedTokenData::Leaf(range.clone()),
+            TokenData::Error(range, error) => OwnedTokenData::Error(range.clone(), error.clone()),
+            TokenData::Branch(children) => OwnedTokenData::Branch(
+                children
+                    .iter()
+                    .map(|(name, child)| (name.clone(), child.to_owned_with(copies)))
+                    .collect(),
+            ),
+        };
+        OwnedToken {
+            source,
+            file: self.file,
+            rule,
+            alt: self.alt,
+            data,
+        }
+    }
+}
+
+fn share<'s>(copies: &mut Vec<(&'s str, Arc<str>)>, text: &'s str) -> Arc<str> {
+    match copies.iter().find(|(s, _)| same_str(s, text)) {
+        Some((_, copy)) => copy.clone(),
+        None => {
+            let copy: Arc<str> = text.into();
+            copies.push((text, copy.clone()));
+            copy
+        }
+    }
+}
+
+fn same_str(a: &str, b: &str) -> bool {
+    std::ptr::eq(a, b)
+}
+
+impl From<&Token<'_>> for OwnedToken {
+    fn from(token: &Token<'_>) -> Self {
+        token.to_owned_token()
+    }
+}
+
+impl OwnedToken {
+    pub fn get_children(&self, key: &str) -> Vec<OwnedToken> {
+        self.children_named(key).cloned().collect()
+    }
+
+    pub fn get_first_child(&self, key: &str) -> Option<OwnedToken> {
+        self.child(key).cloned()
+    }
+
+    // A borrowed copy of the tree, for APIs that take a `Token`. This builds
+    // the whole tree again; navigation works on `OwnedToken` directly.
+    pub fn as_token(&self) -> Token<'_> {
+        let data = match &self.data {
+            OwnedTokenData::Leaf(range) => TokenData::Leaf(range.clone()),
+            OwnedTokenData::Error(range, error) => TokenData::Error(range.clone(), error.clone()),
+            OwnedTokenData::Branch(children) => TokenData::Branch(
+                children
+                    .iter()
+                    .map(|(name, child)| (name.clone(), child.as_token()))
+                    .collect(),
+            ),
+        };
+        Token {
+            source: &self.source,
+            file: self.file,
+            rule: self.rule.as_deref(),
+            alt: self.alt,
+            value: None,
+            data,
+        }
+    }
+}
+
+impl TokenNode for OwnedToken {
+    fn source(&self) -> &str {
+        &self.source
+    }
+
+    fn file(&self) -> FileId {
+        self.file
+    }
+
+    fn rule(&self) -> Option<&str> {
+        self.rule.as_deref()
+    }
+
+    fn alt(&self) -> Option<usize> {
+        self.alt
+    }
+
+    fn range(&self) -> Option<Range<usize>> {
+        match &self.data {
+            OwnedTokenData::Leaf(range) | OwnedTokenData::Error(range, _) => Some(range.clone()),
+            OwnedTokenData::Branch(_) => None,
+        }
+    }
+
+    fn error(&self) -> Option<&ParseError> {
+        match &self.data {
+            OwnedTokenData::Error(_, error) => Some(error),
+            _ => None,
+        }
+    }
+
+    fn entries(&self) -> &[(String, Self)] {
+        match &self.data {
+            OwnedTokenData::Leaf(_) | OwnedTokenData::Error(..) => &[],
+            OwnedTokenData::Branch(children) => children,
+        }
+    }
+}
+
+impl Debug for OwnedToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.graph())
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use super::*;
+use crate::corpus::*;
+use crate::meta::*;
+use crate::sequence::*;
+
+#[test]
+pub fn owned_test() {
+    let refs = eval_rule_set("pair = a..z+:key & '=' & 0..9+:value");
+    let owned = {
+        let text = String::from("abc=123");
+        let token = refs["pair"]
+            .match_tokens(&Corpus::make(&text).tokens, &refs)
+            .unwrap()
+            .new_token;
+        let owned = token.to_owned_token();
+        assert_eq!(owned.graph(), token.graph());
+        owned
+    };
+    let owned = std::thread::spawn(move || owned).join().unwrap();
+    assert_eq!(owned.content(), "abc=123");
+    // Navigation borrows from the owned tree, as it does from a `Token`.
+    let key: &OwnedToken = owned.child("key").unwrap();
+    assert_eq!(key.content(), "abc");
+    assert_eq!(
+        owned.children_named("value").next().unwrap().content(),
+        "123"
+    );
+    assert!(owned.child("missing").is_none());
+    assert_eq!(owned.child_at(1).unwrap().content(), "=");
+    assert_eq!(owned.name_at(2), Some("value"));
+    assert_eq!(owned["value"].content(), "123");
+    assert_eq!(
+        owned.descendants().filter(|t| t.range().is_some()).count(),
+        7
+    );
+    assert!(owned.errors().is_empty());
+    assert_eq!(owned.as_token().content_range(), Some(0..7));
+    // The cloning getters work on owned trees too.
+    let key: OwnedToken = owned.get_first_child("key").unwrap();
+    assert_eq!(key.content(), "abc");
+    assert!(owned.get_first_child("missing").is_none());
+    let values = owned.get_children("value");
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0].content(), "123");
+}
+
+#[test]
+pub fn shared_source_test() {
+    let text: Arc<str> = "ab".into();
+    let refs = eval_rule_set("main = a..z+");
+    let corpus = Corpus::make(&text);
+    let token = refs["main"].match_tokens(&corpus.tokens, &refs).unwrap();
+    let owned = token
+        .new_token
+        .to_owned_token_in(std::slice::from_ref(&text));
+    assert!(Arc::ptr_eq(&owned.source, &text));
+    let OwnedTokenData::Branch(children) = &owned.data else {
+        panic!("expected a branch");
+    };
+    assert!(children.iter().all(|(_, c)| Arc::ptr_eq(&c.source, &text)));
+}
+
+#[test]
+pub fn owned_spans_test() {
+    let mut tokens = Corpus::make("ab").tokens;
+    let include = String::from("cd");
+    tokens.extend(Corpus::make_in(&include, FileId(1)).tokens);
+    let refs = eval_rule_set("main = a..z+");
+    let owned = refs["main"]
+        .match_corpus_first(&Corpus::new(tokens), &refs)
+        .unwrap()
+        .new_token
+        .to_owned_token();
+    assert_eq!(owned.content_range(), None);
+    assert_eq!(
+        owned
+            .spans()
+            .iter()
+            .map(|s| (s.file, s.text()))
+            .collect::<Vec<_>>(),
+        vec![(FileId(0), "ab"), (FileId(1), "cd")]
+    );
+}
+
+#[test]
+pub fn navigation_test() {
+    let refs = eval_rule_set("pair = a..z+:key & '=' & (0..9:digit)+:value");
+    let corpus = Corpus::make("ab=12");
+    let token = refs["pair"]
+        .match_tokens(&corpus.tokens, &refs)
+        .unwrap()
+        .new_token;
+
+    assert_eq!(token.children().len(), 3);
+    assert_eq!(token.child("key").map(|t| t.content()), Some("ab"));
+    assert!(token.child("missing").is_none());
+    assert_eq!(token.children_named("value").count(), 1);
+    assert_eq!(token.child_at(1).map(|t| t.content()), Some("="));
+    assert_eq!(token.name_at(2), Some("value"));
+    assert!(token.child_at(3).is_none());
+    assert_eq!(token["value"][1].content(), "2");
+    assert_eq!(token[0].content(), "ab");
+
+    let leaves = |tokens: Vec<&Token<'_>>| {
+        tokens
+            .into_iter()
+            .filter(|t| matches!(t.data, TokenData::Leaf(_)))
+            .map(|t| t.content())
+            .collect::<String>()
+    };
+    let pre: Vec<_> = token.descendants().collect();
+    let post: Vec<_> = token.descendants_post_order().collect();
+    assert_eq!(pre.len(), post.len());
+    assert_eq!(leaves(pre.clone()), "ab=12");
+    assert_eq!(leaves(post.clone()), "ab=12");
+    assert!(std::ptr::eq(pre[0], &token));
+    assert!(std::ptr::eq(*post.last().unwrap(), &token));
+    assert_eq!(pre[1].content(), "ab");
+    assert_eq!(post[0].content(), "a");
+}
+
+#[test]
+#[should_panic(expected = "no child named \"missing\"")]
+pub fn index_panic_test() {
+    let refs = eval_rule_set("main = 'a'+");
+    let corpus = Corpus::make("a");
+    let token = refs["main"].match_tokens(&corpus.tokens, &refs).unwrap();
+    let _ = &token.new_token["missing"];
+}
+
+#[test]
+pub fn rule_test() {
+    let refs = eval_rule_set(include_str!("../tests/left.tuckey"));
+    let corpus = Corpus::make("1+2*3");
+    let expr = RefSeq::new("expr".to_string());
+    let token = expr.parse_corpus(&corpus, &refs).unwrap();
+    assert_eq!(token.rule, Some("expr"));
+    assert_eq!(token.alt, Some(0));
+    let binary = &token["binary"];
+    assert_eq!(binary["lhs"].rule, Some("expr"));
+    assert_eq!(binary["lhs"].alt, Some(1));
+    assert_eq!(binary["rhs"].rule, Some("multExpr"));
+    assert_eq!(binary["rhs"]["binary"]["rhs"].rule, Some("posInt"));
+    assert_eq!(binary["oper"].rule, None);
+    // Leaves made by a rule, like `nonzero`, are not tagged.
+    let int = &binary["rhs"]["binary"]["rhs"];
+    assert_eq!(int[0].rule, None);
+    assert_eq!(int.graph(), "\nposInt {\n\t\"3\",\n\t{\n\t},\n}");
+    let graph = token.graph();
+    assert!(graph.starts_with("\nexpr|0 {\n\tbinary:\n\t{\n\t\tlhs:\n\t\texpr|1 {"));
+
+    // `b = a` passes on the branch of `a`, and the outer rule wins.
+    let corpus = Corpus::make("yx");
+    let b = RefSeq::new("b".to_string());
+    let token = b.parse_corpus(&corpus, &refs).unwrap();
+    assert_eq!(token.rule, Some("b"));
+    assert_eq!(token[0][0].rule, Some("b"));
+
+    let owned = token.to_owned_token();
+    assert_eq!(owned.rule.as_deref(), Some("b"));
+    assert_eq!(owned.graph(), token.graph());
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use crate::token::*;
+
+#[cfg(test)]
+mod tests;
+
+// What a walk does after a callback: carry on, leave out the children of the
+// token just entered (its `leave` is still called), or end the walk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    Continue,
+    Skip,
+    Stop,
+}
+
+// Callbacks for a depth-first walk. `name` is the name the token has under
+// its parent, or `""` for the root.
+pub trait TokenVisitor<'a> {
+    fn enter(&mut self, _name: &str, _token: &Token<'a>) -> Visit {
+        Visit::Continue
+    }
+
+    fn leave(&mut self, _name: &str, _token: &Token<'a>) -> Visit {
+        Visit::Continue
+    }
+}
+
+// Builds a value for a tree from the leaves up: each token is folded once the
+// values of all its children are known, in order and with their names.
+pub trait TokenFold<'a, T> {
+    fn fold(&mut self, name: &str, token: &Token<'a>, children: Vec<(&str, T)>) -> T;
+}
+
+type FoldFn<'f, 'a, T> = Box<dyn for<'t> FnMut(&'t Token<'a>, Vec<(&'t str, T)>) -> T + 'f>;
+type DefaultFn<'f, 'a, T> =
+    Box<dyn for<'t> FnMut(&'t str, &'t Token<'a>, Vec<(&'t str, T)>) -> T + 'f>;
+
+// A fold made of closures keyed on child names, with one for everything else.
+pub struct NamedFold<'f, 'a, T> {
+    handlers: HashMap<String, FoldFn<'f, 'a, T>>,
+    default: DefaultFn<'f, 'a, T>,
+}
+
+impl<'f, 'a, T> NamedFold<'f, 'a, T> {
+    pub fn new(
+        default: impl for<'t> FnMut(&'t str, &'t Token<'a>, Vec<(&'t str, T)>) -> T + 'f,
+    ) -> Self {
+        Self {
+            handlers: HashMap::new(),
+            default: Box::new(default),
+        }
+    }
+
+    pub fn on(
+        mut self,
+        name: impl Into<String>,
+        handler: impl for<'t> FnMut(&'t Token<'a>, Vec<(&'t str, T)>) -> T + 'f,
+    ) -> Self {
+        self.handlers.insert(name.into(), Box::new(handler));
+        self
+    }
+}
+
+impl<'a, T> TokenFold<'a, T> for NamedFold<'_, 'a, T> {
+    fn fold(&mut self, name: &str, token: &Token<'a>, children: Vec<(&str, T)>) -> T {
+        match self.handlers.get_mut(name) {
+            Some(handler) => handler(token, children),
+            None => (self.default)(name, token, children),
+        }
+    }
+}
+
+impl<'a> Token<'a> {
+    // Returns `Visit::Stop` if the visitor stopped the walk.
+    pub fn walk(&self, visitor: &mut impl TokenVisitor<'a>) -> Visit {
+        self.walk_named("", visitor)
+    }
+
+    fn walk_named(&self, name: &str, visitor: &mut impl TokenVisitor<'a>) -> Visit {
+        match visitor.enter(name, self) {
+            Visit::Stop => return Visit::Stop,
+            Visit::Skip => {}
+            Visit::Continue => {
+                for (child_name, child) in self.entries() {
+                    if child.walk_named(child_name, visitor) == Visit::Stop {
+                        return Visit::Stop;
+                    }
+                }
+            }
+        }
+        match visitor.leave(name, self) {
+            Visit::Stop => Visit::Stop,
+            _ => Visit::Continue,
+        }
+    }
+
+    pub fn fold<T>(&self, folder: &mut impl TokenFold<'a, T>) -> T {
+        self.fold_named("", folder)
+    }
+
+    fn fold_named<T>(&self, name: &str, folder: &mut impl TokenFold<'a, T>) -> T {
+        let children = self
+            .entries()
+            .iter()
+            .map(|(child_name, child)| (child_name.as_str(), child.fold_named(child_name, folder)))
+            .collect();
+        folder.fold(name, self, children)
+    }
+}
@@ -0,0 +1,109 @@
+use super::*;
+use crate::corpus::*;
+use crate::meta::*;
+use crate::sequence::*;
+
+fn calc_seqs() -> RefMap {
+    eval_rule_set(include_str!("../tests/left.tuckey"))
+}
+
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+    skip: &'static str,
+    stop: &'static str,
+}
+
+impl TokenVisitor<'_> for Recorder {
+    fn enter(&mut self, name: &str, token: &Token<'_>) -> Visit {
+        if name.is_empty() {
+            return Visit::Continue;
+        }
+        self.events
+            .push(format!("enter {name} {}", token.content()));
+        match name {
+            _ if name == self.skip => Visit::Skip,
+            _ if name == self.stop => Visit::Stop,
+            _ => Visit::Continue,
+        }
+    }
+
+    fn leave(&mut self, name: &str, _token: &Token<'_>) -> Visit {
+        if !name.is_empty() {
+            self.events.push(format!("leave {name}"));
+        }
+        Visit::Continue
+    }
+}
+
+#[test]
+pub fn visit_test() {
+    let seqs = calc_seqs();
+    let corpus = Corpus::make("1 + 2");
+    let expr = RefSeq::new("expr".to_string());
+    let token = expr.parse_corpus(&corpus, &seqs).unwrap();
+
+    let mut skipping = Recorder {
+        skip: "lhs",
+        ..Default::default()
+    };
+    assert_eq!(token.walk(&mut skipping), Visit::Continue);
+    assert_eq!(
+        skipping.events,
+        [
+            "enter binary 1 + 2",
+            "enter lhs 1",
+            "leave lhs",
+            "enter oper +",
+            "enter + +",
+            "leave +",
+            "leave oper",
+            "enter rhs 2",
+            "enter single 2",
+            "leave single",
+            "leave rhs",
+            "leave binary",
+        ]
+    );
+
+    let mut stopping = Recorder {
+        stop: "oper",
+        ..Default::default()
+    };
+    assert_eq!(token.walk(&mut stopping), Visit::Stop);
+    assert_eq!(stopping.events.last().unwrap(), "enter oper +");
+    assert!(!stopping.events.iter().any(|e| e.starts_with("enter rhs")));
+}
+
+#[test]
+pub fn fold_test() {
+    let seqs = calc_seqs();
+    let corpus = Corpus::make("1 + 2 * 34 - 5");
+    let expr = RefSeq::new("expr".to_string());
+    let token = expr.parse_corpus(&corpus, &seqs).unwrap();
+
+    // Renders the tree in prefix form, passing single children through.
+    let mut prefix =
+        NamedFold::new(
+            |_, token, mut children: Vec<(&str, String)>| match children.len() {
+                1 => children.pop().unwrap().1,
+                _ => token.content().to_string(),
+            },
+        )
+        .on("binary", |_, children| {
+            let get = |key| &children.iter().find(|(name, _)| *name == key).unwrap().1;
+            format!("({} {} {})", get("oper"), get("lhs"), get("rhs"))
+        });
+    assert_eq!(token.fold(&mut prefix), "(- (+ 1 (* 2 34)) 5)");
+
+    struct LeafCount;
+    impl TokenFold<'_, usize> for LeafCount {
+        fn fold(&mut self, _: &str, token: &Token<'_>, children: Vec<(&str, usize)>) -> usize {
+            match token.data {
+                TokenData::Leaf(_) | TokenData::Error(..) => 1,
+                TokenData::Branch(_) => children.iter().map(|(_, n)| n).sum(),
+            }
+        }
+    }
+    assert_eq!(token.fold(&mut LeafCount), corpus.tokens.len());
+}
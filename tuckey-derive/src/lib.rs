@@ -0,0 +1,202 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Fields, GenericParam,
+    Lifetime, LifetimeParam, LitStr, Result,
+};
+
+// Maps a token onto a struct or enum.
+//
+// Struct fields take one of these attributes:
+// - `#[tuckey(child = "name")]`: the first child called `name`; missing
+//   children are an error unless the field is an `Option`.
+// - `#[tuckey(children = "name")]`: every child called `name`, collected.
+// - `#[tuckey(text)]`: the whole token, e.g. its content as a `String`.
+// - `#[tuckey(span)]`: the byte range of the token.
+// Named fields without an attribute use the child with the field's name, and
+// a lone unnamed field without one uses the whole token.
+//
+// Enums match the name of the single child left by a `ChooseSeq`, which is
+// the variant name unless overridden with `#[tuckey(child = "name")]`, and
+// then build the variant's fields from that child.
+#[proc_macro_derive(FromToken, attributes(tuckey))]
+pub fn derive_from_token(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+enum Source {
+    Child(LitStr),
+    Children(LitStr),
+    Text,
+    Span,
+}
+
+fn parse_attrs(attrs: &[Attribute]) -> Result<Option<(Source, Span)>> {
+    let mut found: Option<(Source, Span)> = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("tuckey")) {
+        attr.parse_nested_meta(|meta| {
+            let source = if meta.path.is_ident("child") {
+                Source::Child(meta.value()?.parse()?)
+            } else if meta.path.is_ident("children") {
+                Source::Children(meta.value()?.parse()?)
+            } else if meta.path.is_ident("text") {
+                Source::Text
+            } else if meta.path.is_ident("span") {
+                Source::Span
+            } else {
+                return Err(meta.error(
+                    "expected one of `child = \"..\"`, `children = \"..\"`, `text` or `span`",
+                ));
+            };
+            if found.is_some() {
+                return Err(meta.error("only one `tuckey` attribute is allowed here"));
+            }
+            found = Some((source, meta.path.span()));
+            Ok(())
+        })?;
+    }
+    Ok(found)
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream> {
+    if let Some((_, span)) = parse_attrs(&input.attrs)? {
+        return Err(Error::new(
+            span,
+            "`tuckey` attributes belong on fields and variants",
+        ));
+    }
+
+    let name = &input.ident;
+    let body = match &input.data {
+        Data::Struct(data) => build(quote!(#name), &data.fields)?,
+        Data::Enum(data) => {
+            let mut arms = vec![];
+            let mut names = vec![];
+            for variant in &data.variants {
+                let child = match parse_attrs(&variant.attrs)? {
+                    None => LitStr::new(&variant.ident.to_string(), variant.ident.span()),
+                    Some((Source::Child(child), _)) => child,
+                    Some((_, span)) => {
+                        return Err(Error::new(span, "variants only take `child = \"..\"`"))
+                    }
+                };
+                let ident = &variant.ident;
+                let build = build(quote!(#name::#ident), &variant.fields)?;
+                arms.push(quote! {
+                    #child => {
+                        let token = child;
+                        #build
+                    }
+                });
+                names.push(format!("`{}`", child.value()));
+            }
+            let expected = format!("expected one of {}, found `{{}}`", names.join(", "));
+            quote! {
+                let error = || ::tuckey::ast::AstError::at(
+                    format!(#expected, ::tuckey::ast::content(token)),
+                    token,
+                );
+                match &token.data {
+                    ::tuckey::token::TokenData::Branch(children) if children.len() == 1 => {
+                        let (name, child) = &children[0];
+                        match name.as_str() {
+                            #(#arms)*
+                            _ => Err(error()),
+                        }
+                    }
+                    _ => Err(error()),
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                "`FromToken` cannot be derived for unions",
+            ))
+        }
+    };
+
+    let mut generics = input.generics.clone();
+    let lifetime = match generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lifetime = Lifetime::new("'tuckey", Span::call_site());
+            generics.params.insert(
+                0,
+                GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())),
+            );
+            lifetime
+        }
+    };
+    let params: Vec<_> = generics
+        .type_params()
+        .map(|param| param.ident.clone())
+        .collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#param: ::tuckey::ast::FromToken<#lifetime>));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::tuckey::ast::FromToken<#lifetime> for #name #ty_generics #where_clause {
+            fn from_token(
+                token: &::tuckey::token::Token<#lifetime>,
+            ) -> ::core::result::Result<Self, ::tuckey::ast::AstError> {
+                #body
+            }
+        }
+    })
+}
+
+fn build(path: TokenStream, fields: &Fields) -> Result<TokenStream> {
+    let lone = fields.len() == 1;
+    let mut values = vec![];
+    for field in fields {
+        let source = match (parse_attrs(&field.attrs)?, &field.ident) {
+            (Some((source, _)), _) => source,
+            (None, Some(ident)) => {
+                let name = ident.to_string();
+                Source::Child(LitStr::new(name.trim_start_matches("r#"), ident.span()))
+            }
+            (None, None) if lone => Source::Text,
+            (None, None) => {
+                return Err(Error::new(
+                    field.span(),
+                    "unnamed fields need a `tuckey` attribute unless there is only one",
+                ))
+            }
+        };
+        let ty = &field.ty;
+        let value = match source {
+            Source::Child(name) => quote_spanned! {ty.span()=>
+                ::tuckey::ast::from_child::<#ty>(token, #name)?
+            },
+            Source::Children(name) => quote_spanned! {ty.span()=>
+                ::tuckey::ast::from_children::<_, #ty>(token, #name)?
+            },
+            Source::Text => quote_spanned! {ty.span()=>
+                <#ty as ::tuckey::ast::FromToken>::from_token(token)?
+            },
+            Source::Span => quote_spanned! {ty.span()=>
+                ::tuckey::ast::span(token)
+            },
+        };
+        values.push(match &field.ident {
+            Some(ident) => quote!(#ident: #value),
+            None => value,
+        });
+    }
+    Ok(match fields {
+        Fields::Named(_) => quote!(Ok(#path { #(#values),* })),
+        Fields::Unnamed(_) => quote!(Ok(#path(#(#values),*))),
+        Fields::Unit => quote!(Ok(#path)),
+    })
+}
@@ -0,0 +1,251 @@
+use core::ops::Range;
+
+use tuckey::ast::*;
+use tuckey::corpus::*;
+use tuckey::meta::*;
+use tuckey::sequence::*;
+
+fn calc_seqs() -> RefMap {
+    eval_rule_set(
+        "
+    nonzero = 1..9
+    digit = 0..9
+    posInt = nonzero:first & digit*
+    oper = [+-]
+    multOper = [*/]
+    expr =
+        multExpr:lhs +
+        (_ + oper:oper + multExpr:rhs).rhs_s*:rhs_s
+    multExpr =
+        numExpr:lhs +
+        (_ + multOper:oper + numExpr:rhs).rhs_s*:rhs_s
+    numExpr = posInt
+    ",
+    )
+}
+
+#[derive(Debug, FromToken)]
+struct Expr {
+    lhs: MultExpr,
+    #[tuckey(child = "rhs_s")]
+    rest: Vec<Op<Oper, MultExpr>>,
+}
+
+#[derive(Debug, FromToken)]
+struct MultExpr {
+    lhs: Num,
+    #[tuckey(child = "rhs_s")]
+    rest: Vec<Op<MultOper, Num>>,
+}
+
+#[derive(Debug, FromToken)]
+struct Op<O, T> {
+    oper: O,
+    rhs: T,
+}
+
+#[derive(Debug, FromToken)]
+enum Oper {
+    #[tuckey(child = "+")]
+    Add,
+    #[tuckey(child = "-")]
+    Sub,
+}
+
+#[derive(Debug, FromToken)]
+enum MultOper {
+    #[tuckey(child = "*")]
+    Mul,
+    #[tuckey(child = "/")]
+    Div,
+}
+
+#[derive(Debug, FromToken)]
+struct Num(f64);
+
+impl Expr {
+    fn eval(&self) -> f64 {
+        self.rest
+            .iter()
+            .fold(self.lhs.eval(), |acc, op| match op.oper {
+                Oper::Add => acc + op.rhs.eval(),
+                Oper::Sub => acc - op.rhs.eval(),
+            })
+    }
+}
+
+impl MultExpr {
+    fn eval(&self) -> f64 {
+        self.rest.iter().fold(self.lhs.0, |acc, op| match op.oper {
+            MultOper::Mul => acc * op.rhs.0,
+            MultOper::Div => acc / op.rhs.0,
+        })
+    }
+}
+
+fn eval(body: &str) -> f64 {
+    let seqs = calc_seqs();
+    let corpus = Corpus::make(body);
+    let token = seqs["expr"].parse_corpus(&corpus, &seqs).unwrap();
+    Expr::from_token(&token).unwrap().eval()
+}
+
+#[test]
+fn eval_test() {
+    assert_eq!(eval("1"), 1.0);
+    assert_eq!(eval("1234"), 1234.0);
+    assert_eq!(eval("1 + 2"), 3.0);
+    assert_eq!(eval("1 + 2 / 2"), 2.0);
+    assert_eq!(eval("1 / 2"), 0.5);
+    assert_eq!(eval("8 - 2 * 3 - 1"), 1.0);
+}
+
+fn item_seqs() -> RefMap {
+    eval_rule_set(
+        "
+    letter = a..z
+    digit = 0..9
+    name = letter+
+    number = digit+
+    item = name:name & (_ + '=' + number:n)?:count
+    list = (_ + item:item).items*:items
+    version = number:part & '.' & number:part & '.' & number:part
+    sign = '+'.plus | '-'.minus
+    signed = sign:sign & number:n
+    maybe = sign?:sign & number:n
+    amount = number.n | '?'.unknown
+    bracketed = '[' & amount:amount & ']'
+    ",
+    )
+}
+
+#[derive(Debug, PartialEq, FromToken)]
+struct List<'a> {
+    #[tuckey(children = "items")]
+    items: Vec<Entry<'a>>,
+}
+
+#[derive(Debug, PartialEq, FromToken)]
+struct Entry<'a> {
+    item: Item<'a>,
+}
+
+#[derive(Debug, PartialEq, FromToken)]
+struct Item<'a> {
+    name: &'a str,
+    count: Option<Count>,
+    #[tuckey(span)]
+    span: Range<usize>,
+    #[tuckey(text)]
+    text: String,
+}
+
+#[derive(Debug, PartialEq, FromToken)]
+struct Count {
+    n: u32,
+}
+
+#[derive(Debug, PartialEq, FromToken)]
+struct Version {
+    #[tuckey(children = "part")]
+    parts: Vec<u32>,
+}
+
+#[test]
+fn attribute_test() {
+    let seqs = item_seqs();
+    let text = "apple = 3\n pear";
+    let token = seqs["list"]
+        .parse_corpus(&Corpus::make(text), &seqs)
+        .unwrap();
+    let list = List::from_token(&token).unwrap();
+    assert_eq!(
+        list.items,
+        vec![
+            Entry {
+                item: Item {
+                    name: "apple",
+                    count: Some(Count { n: 3 }),
+                    span: 0..9,
+                    text: "apple = 3".to_string(),
+                },
+            },
+            Entry {
+                item: Item {
+                    name: "pear",
+                    count: None,
+                    span: 11..15,
+                    text: "pear".to_string(),
+                },
+            },
+        ]
+    );
+}
+
+#[test]
+fn children_test() {
+    let seqs = item_seqs();
+    let corpus = Corpus::make("1.20.3");
+    let token = seqs["version"].parse_corpus(&corpus, &seqs).unwrap();
+    let version = Version::from_token(&token).unwrap();
+    assert_eq!(version.parts, vec![1, 20, 3]);
+}
+
+#[test]
+fn missing_child_test() {
+    #[derive(Debug, FromToken)]
+    struct Wrong {
+        #[allow(dead_code)]
+        colour: String,
+    }
+
+    let seqs = item_seqs();
+    let corpus = Corpus::make("pear");
+    let token = seqs["item"].parse_corpus(&corpus, &seqs).unwrap();
+    let err = Wrong::from_token(&token).unwrap_err();
+    assert_eq!(err.to_string(), "missing child `colour` at 0..4");
+    assert_eq!(err.span, Some(0..4));
+}
+
+#[test]
+fn wrong_variant_test() {
+    let seqs = calc_seqs();
+    let corpus = Corpus::make("7");
+    let token = seqs["posInt"].parse_corpus(&corpus, &seqs).unwrap();
+    let err = Oper::from_token(&token).unwrap_err();
+    assert_eq!(err.message, "expected one of `+`, `-`, found `7`");
+}
+
+#[derive(Debug, PartialEq, FromToken)]
+enum Sign {
+    #[tuckey(child = "plus")]
+    Plus,
+    #[tuckey(child = "minus")]
+    Minus,
+}
+
+#[derive(Debug, PartialEq, FromToken)]
+struct Signed {
+    sign: Option<Sign>,
+    n: u32,
+}
+
+#[derive(Debug, PartialEq, FromToken)]
+struct Bracketed {
+    amount: Option<Count>,
+}
+
+#[test]
+fn option_test() {
+    let seqs = item_seqs();
+    let parse = |rule: &str, text| seqs[rule].parse_corpus(&Corpus::make(text), &seqs).unwrap();
+    let signed = Signed::from_token(&parse("signed", "-4")).unwrap();
+    assert_eq!(signed.sign, Some(Sign::Minus));
+    let maybe = Signed::from_token(&parse("maybe", "+4")).unwrap();
+    assert_eq!(maybe.sign, Some(Sign::Plus));
+    let maybe = Signed::from_token(&parse("maybe", "4")).unwrap();
+    assert_eq!(maybe.sign, None);
+    // The choice inside `amount` leaves a single `n`, which is `Count`'s.
+    let bracketed = Bracketed::from_token(&parse("bracketed", "[12]")).unwrap();
+    assert_eq!(bracketed.amount, Some(Count { n: 12 }));
+}
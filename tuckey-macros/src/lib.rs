@@ -0,0 +1,122 @@
+use std::ops::Range;
+use std::path::PathBuf;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_macro_input, Error, LitStr, Result};
+use tuckey::codegen::generate_parser;
+
+// Compiles a grammar written in meta syntax, given as a string literal, into
+// a `tuckey::sequence::Grammar`. Mistakes in the grammar are compile errors.
+//
+//     const CALC: Grammar = grammar!(r"
+//         digit = 0..9
+//         number = digit+
+//     ");
+#[proc_macro]
+pub fn grammar(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let text = parse_macro_input!(input as LitStr);
+    expand(&text.value(), &text, None)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+// Like `grammar!`, but reads the grammar from a file, relative to the
+// directory of the crate's `Cargo.toml`.
+#[proc_macro]
+pub fn include_grammar(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+    include(&path)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn include(path: &LitStr) -> Result<TokenStream> {
+    let root = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full = PathBuf::from(root).join(path.value());
+    let text = std::fs::read_to_string(&full)
+        .map_err(|err| Error::new(path.span(), format!("{}: {err}", full.display())))?;
+    expand(&text, path, Some(full.to_string_lossy().into_owned()))
+}
+
+// `file`, when set, is included as a string so that cargo rebuilds the crate
+// when the grammar changes. Errors in a literal point at the mistake itself
+// where the compiler allows; errors in a file can only name it.
+fn expand(text: &str, origin: &LitStr, file: Option<String>) -> Result<TokenStream> {
+    let code = generate_parser(text).map_err(|err| match &file {
+        Some(file) => Error::new(origin.span(), format!("{file}:{err}")),
+        None => Error::new(
+            error_span(origin, err.span.clone()),
+            format!("grammar error at {err}"),
+        ),
+    })?;
+    let code: TokenStream = code.parse()?;
+    let track = file.map(|file| {
+        quote!(
+            const _: &str = include_str!(#file);
+        )
+    });
+    Ok(quote! {
+        {
+            #track
+            #code
+            ::tuckey::sequence::Grammar::new(RULES)
+        }
+    })
+}
+
+// The part of the literal `origin` that holds `range` of its value, for
+// literals without escapes, whose value appears in the source as written.
+// Compilers that cannot point inside a literal (stable ones, for now) get
+// the whole literal.
+fn error_span(origin: &LitStr, range: Range<usize>) -> Span {
+    let literal = origin.token();
+    let Some(start) = value_offset(&literal.to_string(), &origin.value()) else {
+        return origin.span();
+    };
+    let end = range.end.max(range.start + 1);
+    literal
+        .subspan(start + range.start..start + end)
+        .unwrap_or_else(|| origin.span())
+}
+
+// Where `value` starts in the source of a string literal, whether plain
+// (`"..."`) or raw (`r#"..."#`).
+fn value_offset(source: &str, value: &str) -> Option<usize> {
+    let start = source.find('"')? + 1;
+    let body = source.get(start..)?;
+    let closing = &source[..start - 1].trim_start_matches('r');
+    let end = body.len().checked_sub(1 + closing.len())?;
+    (body.get(..end)? == value).then_some(start)
+}
+
+#[cfg(test)]
+mod tests {
+    use proc_macro2::Span;
+
+    use super::*;
+
+    #[test]
+    pub fn value_offset_test() {
+        assert_eq!(value_offset("\"main 'a'\"", "main 'a'"), Some(1));
+        assert_eq!(value_offset("r#\"main 'a'\"#", "main 'a'"), Some(3));
+        assert_eq!(value_offset("r\"a\"", "a"), Some(2));
+        // Escapes mean the value is not in the source as written.
+        assert_eq!(value_offset("\"a\\nb\"", "a\nb"), None);
+    }
+
+    #[test]
+    pub fn error_test() {
+        let origin = LitStr::new("", Span::call_site());
+        let err = expand("main 'a'", &origin, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "grammar error at 1:6: expected `=`, found `'` (in rule `main`)"
+        );
+        let err = expand("main 'a'", &origin, Some("calc.tuckey".to_string())).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "calc.tuckey:1:6: expected `=`, found `'` (in rule `main`)"
+        );
+    }
+}
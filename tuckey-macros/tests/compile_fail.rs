@@ -0,0 +1,5 @@
+#[test]
+pub fn compile_fail_test() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,69 @@
+use tuckey::corpus::*;
+use tuckey::meta::*;
+use tuckey::sequence::*;
+use tuckey::token::*;
+use tuckey_macros::{grammar, include_grammar};
+
+const CALC: Grammar = include_grammar!("../src/tests/calc.tuckey");
+
+const LIST: Grammar = grammar!(
+    r"
+    digit = 0..9
+    number = digit+
+    list = '[' & (number:item & (',' + number:item).rest*:rest)?:items & ']'
+    "
+);
+
+// Rules named like the locals of the generated code.
+const LOCALS: Grammar = grammar!(
+    r"
+    pos = 'a'
+    len = 'c'
+    ctx = 'd'?
+    tokens = 'e'*
+    children = pos & len
+    matched = 'f' % ','
+    at = 'g'{1,2}
+    m = ('h' & ';')@';'
+    main = children & ctx & tokens & matched & at & m & 'b'
+    "
+);
+
+#[test]
+pub fn include_test() {
+    let seqs = eval_rule_set(include_str!("../../src/tests/calc.tuckey"));
+    let expr = RefSeq::new("expr".to_string());
+    for text in ["1 + 2 * (3 - 4)", "1 + * 2", ""] {
+        let corpus = Corpus::make(text);
+        let interpreted = expr.parse_corpus(&corpus, &seqs);
+        let compiled = CALC.rule("expr").unwrap().parse(&corpus);
+        assert_eq!(
+            interpreted.map(|t| t.to_sexpr()).map_err(|e| e.to_string()),
+            compiled.map(|t| t.to_sexpr()).map_err(|e| e.to_string()),
+        );
+    }
+}
+
+#[test]
+pub fn inline_test() {
+    let list = LIST.rule("list").unwrap();
+    let token = list.parse(&Corpus::make("[1, 22, 333]")).unwrap();
+    assert_eq!(token.content(), "[1, 22, 333]");
+    let err = list.parse(&Corpus::make("[1, x]")).unwrap_err();
+    assert_eq!(err.to_string(), "expected number at 1:5 while parsing list");
+    assert_eq!(
+        LIST.rules()
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>(),
+        ["digit", "number", "list"]
+    );
+    assert!(LIST.rule("missing").is_none());
+}
+
+#[test]
+pub fn locals_test() {
+    let main = LOCALS.rule("main").unwrap();
+    let token = main.parse(&Corpus::make("acdef,fgh;b")).unwrap();
+    assert_eq!(token.content(), "acdef,fgh;b");
+}
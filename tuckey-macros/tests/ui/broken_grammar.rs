@@ -0,0 +1,13 @@
+use tuckey::sequence::Grammar;
+use tuckey_macros::grammar;
+
+const BROKEN: Grammar = grammar!(
+    r"
+    digit = 0..9
+    number digit+
+    "
+);
+
+fn main() {
+    let _ = BROKEN;
+}